@@ -0,0 +1,200 @@
+use std::cmp::max;
+
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Timelike, Weekday};
+
+// Alarm of an [alarm.NAME] section, or given with --alarm
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Alarm {
+    pub time: NaiveTime,
+    // Days of the week it goes off, Monday first
+    pub days: [bool; 7],
+    pub label: String,
+    pub bell: bool,
+    pub flash: bool,
+    pub command: Option<String>,
+    pub snooze: Duration,
+}
+
+impl Alarm {
+    // Whether the alarm goes off after `from`, until `to` included. Times are local ones, so
+    // an alarm in the hour skipped when clocks go forward rings once they have, but one in
+    // the hour repeated when they go back rings twice
+    pub fn goes_off_between(&self, from: NaiveDateTime, to: NaiveDateTime) -> bool {
+        // After a long sleep, only the last week matters
+        let mut date = max(from.date(), to.date() - Duration::days(7));
+        while date <= to.date() {
+            let at = date.and_time(self.time);
+            if self.days[date.weekday().num_days_from_monday() as usize] && from < at && at <= to {
+                return true;
+            }
+
+            let Some(next) = date.succ_opt() else {
+                break;
+            };
+            date = next;
+        }
+
+        false
+    }
+}
+
+// How often the clock chimes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChimeInterval {
+    None,
+    Hour,
+    Quarter,
+}
+
+impl ChimeInterval {
+    pub fn from_name(name: &str) -> Option<ChimeInterval> {
+        match name {
+            "none" => Some(ChimeInterval::None),
+            "hour" => Some(ChimeInterval::Hour),
+            "quarter" => Some(ChimeInterval::Quarter),
+            _ => None,
+        }
+    }
+
+    // Whether a chime is due after `from`, until `to` included, like alarms
+    pub fn chimes_between(&self, from: NaiveDateTime, to: NaiveDateTime) -> bool {
+        let minutes = match self {
+            ChimeInterval::None => return false,
+            ChimeInterval::Hour => 60,
+            ChimeInterval::Quarter => 15,
+        };
+
+        // Only the last chime matters, a skipped one rings late rather than several times
+        let minute = to.minute() / minutes * minutes;
+        to.date()
+            .and_hms_opt(to.hour(), minute, 0)
+            .is_some_and(|last| from < last)
+    }
+}
+
+// Chimes of the [chimes] section
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Chimes {
+    pub interval: ChimeInterval,
+    pub bell: bool,
+    // Whether the digits flash in the chime element's style
+    pub flash: bool,
+    pub command: Option<String>,
+    // Sound file, given as the last argument of the player command
+    pub sound: Option<String>,
+    pub player: String,
+}
+
+// Command playing sound files when none is configured
+pub fn default_player() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "afplay"
+    } else {
+        "aplay -q"
+    }
+}
+
+// 07:30 or 07:30:15
+pub fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+        .ok()
+}
+
+// Days and ranges of days, like "mon-fri" or "sat,sun", "daily" or nothing for every day
+pub fn parse_days(value: &str) -> Option<[bool; 7]> {
+    if value.is_empty() || value == "daily" {
+        return Some([true; 7]);
+    }
+
+    let mut days = [false; 7];
+    for part in value.split(',').map(str::trim) {
+        let (first, last) = part.split_once('-').unwrap_or((part, part));
+        let last = last.trim().parse::<Weekday>().ok()?;
+
+        // Ranges can wrap around the week, like fri-mon
+        let mut day = first.trim().parse::<Weekday>().ok()?;
+        days[day.num_days_from_monday() as usize] = true;
+        while day != last {
+            day = day.succ();
+            days[day.num_days_from_monday() as usize] = true;
+        }
+    }
+
+    Some(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // January 1st 2024 is a Monday
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn alarms_go_off_once_on_their_days() {
+        let alarm = Alarm {
+            time: parse_time("07:30").unwrap(),
+            days: parse_days("mon-fri").unwrap(),
+            label: "Wake up".to_owned(),
+            bell: true,
+            flash: true,
+            command: None,
+            snooze: Duration::minutes(5),
+        };
+
+        assert!(alarm.goes_off_between(at(1, 7, 29), at(1, 7, 30)));
+        assert!(!alarm.goes_off_between(at(1, 7, 30), at(1, 7, 31)));
+        // A skipped frame doesn't miss the alarm
+        assert!(alarm.goes_off_between(at(1, 7, 0), at(1, 8, 0)));
+        // Nor does a night in between
+        assert!(alarm.goes_off_between(at(1, 23, 0), at(2, 9, 0)));
+        // Saturday
+        assert!(!alarm.goes_off_between(at(6, 7, 0), at(6, 8, 0)));
+
+        // Clocks going forward from 07:00 to 08:00
+        assert!(alarm.goes_off_between(at(1, 6, 59), at(1, 8, 0)));
+        // Clocks going back from 08:00 to 07:00, the hour after is checked again
+        assert!(!alarm.goes_off_between(at(1, 7, 59), at(1, 7, 0)));
+        assert!(alarm.goes_off_between(at(1, 7, 0), at(1, 7, 30)));
+    }
+
+    #[test]
+    fn days_are_parsed() {
+        let weekend = [false, false, false, false, false, true, true];
+        assert_eq!(parse_days("sat,sun"), Some(weekend));
+        assert_eq!(parse_days("sat-sun"), Some(weekend));
+        assert_eq!(
+            parse_days("fri-mon"),
+            Some([true, false, false, false, true, true, true])
+        );
+        assert_eq!(parse_days("daily"), Some([true; 7]));
+        assert_eq!(parse_days("mon-someday"), None);
+
+        assert!(parse_time("7:30").is_some());
+        assert!(parse_time("25:00").is_none());
+    }
+
+    #[test]
+    fn chimes_ring_once_per_interval() {
+        let hour = ChimeInterval::Hour;
+        assert!(hour.chimes_between(at(1, 13, 59), at(1, 14, 0)));
+        assert!(!hour.chimes_between(at(1, 14, 0), at(1, 14, 1)));
+        assert!(hour.chimes_between(at(1, 13, 50), at(1, 14, 10)));
+        assert!(!hour.chimes_between(at(1, 13, 15), at(1, 13, 30)));
+
+        let quarter = ChimeInterval::Quarter;
+        assert!(quarter.chimes_between(at(1, 13, 14), at(1, 13, 15)));
+        assert!(!quarter.chimes_between(at(1, 13, 15), at(1, 13, 29)));
+        // Clocks going back, like at the end of daylight saving time, don't chime
+        assert!(!quarter.chimes_between(at(1, 14, 0), at(1, 13, 0)));
+        assert!(!ChimeInterval::None.chimes_between(at(1, 13, 0), at(2, 13, 0)));
+    }
+}
@@ -1,43 +1,849 @@
-use std::{any::type_name, fs, path::PathBuf};
+use std::{
+    any::type_name,
+    collections::HashMap,
+    env, fmt, fs,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant, SystemTime},
+};
 
-use crossterm::style::Color;
+use chrono::{
+    format::{Item, StrftimeItems},
+    Duration as TimeDelta, Weekday,
+};
+use chrono_tz::Tz;
+use crossterm::style::{Attribute, Attributes, Color};
+use dirs::{cache_dir, config_dir, home_dir};
 use ini::configparser::ini::Ini;
 
 use crate::{
+    alarm::{self, Alarm, ChimeInterval, Chimes},
     eprintln_quit,
-    modes::debug,
-    rendering::color::{generate_gradient, parse_hex_color, ComputableColor},
+    modes::{debug, world::Zone},
+    palette::{Palette, PaletteSource},
+    rendering::{
+        calendar::{Calendar, Placement},
+        canvas::Style,
+        color::{
+            generate_gradient, parse_hex_color, ColorSupport, ComputableColor, GradientDirection,
+            ANSI_COLORS,
+        },
+        font::Font,
+        frame::{Border, Frame},
+        interpolation::{ColorSpace, Easing},
+        layout::Scaling,
+        Digits, TextStyle,
+    },
 };
 
 pub struct Config {
+    pub profile: Option<String>,
+    pub theme: Option<String>,
     pub be_polite: bool,
     pub fps: u64,
     pub color: ComputableColor,
+    pub background: Option<Color>,
+    pub color_support: ColorSupport,
     pub time_format: String,
     pub date_format: String,
+    // Zone the time is shown in, None if the system one isn't a known zone
+    pub timezone: Option<Tz>,
+    // Clocks of the world mode
+    pub zones: Vec<Zone>,
+    pub alarms: Vec<Alarm>,
+    pub chimes: Chimes,
+    pub calendar: Calendar,
+    pub digits: Digits,
+    pub frame: Frame,
+    pub elements: Elements,
+}
+
+impl Config {
+    // Move every color to the next step of its gradient
+    pub fn update_colors(&mut self) {
+        self.color.update();
+        for style in self.elements.iter_mut() {
+            style.color.update();
+        }
+    }
+}
+
+// Styles of the parts of the screen, each one can be set in the [elements] section
+pub struct Elements {
+    pub digits: TextStyle,
+    pub colon: TextStyle,
+    pub date: TextStyle,
+    pub status: TextStyle,
+    pub laps: TextStyle,
+    // Labels of the world clocks, and titles of the calendar
+    pub header: TextStyle,
+    pub border: TextStyle,
+    // Label of the local zone in the world mode
+    pub highlight: TextStyle,
+    // Digits flashing when the clock chimes
+    pub chime: TextStyle,
+}
+
+impl Elements {
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut TextStyle> {
+        [
+            &mut self.digits,
+            &mut self.colon,
+            &mut self.date,
+            &mut self.status,
+            &mut self.laps,
+            &mut self.header,
+            &mut self.border,
+            &mut self.highlight,
+            &mut self.chime,
+        ]
+        .into_iter()
+    }
+}
+
+// Where a value comes from, errors point to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    // Line of the config file
+    Line(usize),
+    // Flag or environment variable overriding the file, like --set or TLOCK_GENERAL_FPS
+    Override(String),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Line(line) => write!(f, "line {}", line),
+            Location::Override(origin) => write!(f, "{}", origin),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    Unreadable {
+        path: PathBuf,
+        reason: String,
+    },
+    Syntax {
+        reason: String,
+    },
+    MissingKey {
+        section: String,
+        key: String,
+    },
+    UnknownSection {
+        section: String,
+        location: Option<Location>,
+    },
+    UnknownKey {
+        section: String,
+        key: String,
+        location: Option<Location>,
+    },
+    UnknownProfile {
+        name: String,
+        location: Option<Location>,
+    },
+    InvalidValue {
+        section: String,
+        key: String,
+        location: Option<Location>,
+        expected: String,
+        value: String,
+    },
+    // The value points to a file that can't be loaded, like a font
+    InvalidFile {
+        section: String,
+        key: String,
+        location: Option<Location>,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = |location: &Option<Location>| match location {
+            Some(location) => format!("{}: ", location),
+            None => String::new(),
+        };
+
+        match self {
+            ConfigError::Unreadable { path, reason } => {
+                write!(f, "Unable to read {}: {}", path.display(), reason)
+            }
+            ConfigError::Syntax { reason } => {
+                write!(f, "Unable to parse configuration file: {}", reason)
+            }
+            ConfigError::MissingKey { section, key } => {
+                write!(f, "Missing required config key: {}.{}", section, key)
+            }
+            ConfigError::UnknownSection { section, location } => {
+                write!(f, "{}Unknown section: [{}]", at(location), section)
+            }
+            ConfigError::UnknownKey {
+                section,
+                key,
+                location,
+            } => {
+                write!(f, "{}Unknown config key: {}.{}", at(location), section, key)
+            }
+            ConfigError::UnknownProfile { name, location } => {
+                write!(f, "{}Unknown profile: {}", at(location), name)
+            }
+            ConfigError::InvalidValue {
+                section,
+                key,
+                location,
+                expected,
+                value,
+            } => write!(
+                f,
+                "{}Invalid value at {}.{}: Expected {}, got '{}'",
+                at(location),
+                section,
+                key,
+                expected,
+                value
+            ),
+            ConfigError::InvalidFile {
+                section,
+                key,
+                location,
+                reason,
+            } => write!(
+                f,
+                "{}Invalid value at {}.{}: {}",
+                at(location),
+                section,
+                key,
+                reason
+            ),
+        }
+    }
+}
+
+impl ConfigError {
+    // Whether the error comes from the config file, and not from an override
+    pub fn in_file(&self) -> bool {
+        let location = match self {
+            ConfigError::UnknownSection { location, .. }
+            | ConfigError::UnknownKey { location, .. }
+            | ConfigError::UnknownProfile { location, .. }
+            | ConfigError::InvalidValue { location, .. }
+            | ConfigError::InvalidFile { location, .. } => location,
+            _ => &None,
+        };
+
+        !matches!(location, Some(Location::Override(_)))
+    }
 }
 
 const DEFAULT_CONFIG: &str = include_str!("default_config");
 
-pub fn load_from_file(path: PathBuf, debug_mode: bool) -> Config {
+// Keys that can be repeated with an index suffix, like gradient_key_0, gradient_key_1...
+const INDEXED_KEYS: [(&str, &str); 5] = [
+    ("gradient", "gradient_key"),
+    ("gradient", "gradient_easing"),
+    ("world", "zone"),
+    ("world", "zone_label"),
+    ("world", "zone_format"),
+];
+
+// Keys of [alarm.NAME] sections, on top of the ones of the [alarms] section
+const ALARM_KEYS: [&str; 3] = ["time", "days", "label"];
+
+// Value given outside of the config file, as `section.key=value`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Override {
+    pub section: String,
+    pub key: String,
+    pub value: String,
+    // Flag or environment variable it comes from, errors point to it
+    pub origin: String,
+}
+
+impl FromStr for Override {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Override, String> {
+        let (name, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected SECTION.KEY=VALUE, got '{}'", s))?;
+        let (section, key) = name
+            .split_once('.')
+            .ok_or_else(|| format!("expected SECTION.KEY, got '{}'", name))?;
+
+        Ok(Override {
+            section: section.trim().to_lowercase(),
+            key: key.trim().to_lowercase(),
+            value: value.trim().to_owned(),
+            origin: "--set".to_owned(),
+        })
+    }
+}
+
+// Overrides from TLOCK_SECTION_KEY environment variables, like TLOCK_STYLING_COLOR_HEX.
+// Section names don't contain underscores, so the first one separates it from the key
+pub fn env_overrides() -> Vec<Override> {
+    overrides_from_vars(env::vars())
+}
+
+fn overrides_from_vars(vars: impl Iterator<Item = (String, String)>) -> Vec<Override> {
+    let default = default_ini();
+    let mut overrides: Vec<Override> = vars
+        .filter_map(|(name, value)| {
+            let (section, key) = name.strip_prefix("TLOCK_")?.split_once('_')?;
+            let section = section.to_lowercase();
+
+            // Ignore unrelated variables
+            default
+                .get_map_ref()
+                .contains_key(&section)
+                .then(|| Override {
+                    section,
+                    key: key.to_lowercase(),
+                    value,
+                    origin: name.clone(),
+                })
+        })
+        .collect();
+
+    // Keep a stable order, so errors are always reported the same way
+    overrides.sort_by(|a, b| (&a.section, &a.key).cmp(&(&b.section, &b.key)));
+    overrides
+}
+
+// What is loaded on top of the config file
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    // Mode being run, used to pick its default profile
+    pub mode: &'static str,
+    // Profile given on the command line, takes precedence over the mode's one
+    pub profile: Option<String>,
+    pub overrides: Vec<Override>,
+    // Times of the alarms given on the command line
+    pub alarms: Vec<String>,
+}
+
+const BUILTIN_THEMES: [(&str, &str); 5] = [
+    ("catppuccin", include_str!("themes/catppuccin.theme")),
+    ("dracula", include_str!("themes/dracula.theme")),
+    ("gruvbox", include_str!("themes/gruvbox.theme")),
+    ("nord", include_str!("themes/nord.theme")),
+    ("solarized", include_str!("themes/solarized.theme")),
+];
+
+// Keys a theme can set, all keys of the [gradient] and [elements] sections are allowed too
+const THEME_KEYS: [(&str, &str); 11] = [
+    ("styling", "color_mode"),
+    ("styling", "color_term"),
+    ("styling", "color_hex"),
+    ("styling", "color_ansi"),
+    ("styling", "color_wal"),
+    ("styling", "color_xresources"),
+    ("styling", "color_base16"),
+    ("styling", "background"),
+    ("styling", "accent"),
+    ("styling", "shadow"),
+    ("frame", "fill"),
+];
+
+pub fn load_from_file(path: PathBuf, options: &LoadOptions) -> Result<Config, Vec<ConfigError>> {
+    load_from_str(&read_source(&path)?, options)
+}
+
+// Validate a config file, reporting unknown sections and keys on top of the errors that
+// would prevent loading it
+pub fn check_file(path: PathBuf) -> Result<(), Vec<ConfigError>> {
+    let source = read_source(&path)?;
+    let ini = parse_ini(&source)?;
+
+    let mut errors = find_unknown_keys(&ini, &source);
+
+    // Load the base config, then each profile to validate their values too
+    let mut profiles: Vec<Option<String>> = ini
+        .sections()
+        .iter()
+        .filter_map(|section| section.strip_prefix("profile."))
+        .map(|name| Some(name.to_owned()))
+        .collect();
+    profiles.sort();
+    profiles.insert(0, None);
+
+    for profile in profiles {
+        let options = LoadOptions {
+            profile,
+            ..LoadOptions::default()
+        };
+        for error in load_from_str(&source, &options).err().unwrap_or_default() {
+            if !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// Key whose value differs from the default one, values are None when unset
+pub struct KeyDiff {
+    pub section: String,
+    pub key: String,
+    pub default: Option<String>,
+    pub value: Option<String>,
+}
+
+// List the keys of a config file which differ from the defaults, in the order of the
+// default config. Keys it doesn't have come last, in file order
+pub fn diff_file(path: PathBuf) -> Result<Vec<KeyDiff>, Vec<ConfigError>> {
+    let source = read_source(&path)?;
+    let merged = merge_with_defaults(&parse_ini(&source)?);
+    let default = default_ini();
+
+    let default_lines = find_key_lines(DEFAULT_CONFIG);
+    let lines = find_key_lines(&source);
+    let mut diffs = Vec::new();
+    for ini in [&merged, &default] {
+        for (section, keys) in ini.get_map_ref() {
+            for key in keys.keys() {
+                let value = merged.get(section, key);
+                let default = default.get(section, key);
+                let seen = diffs
+                    .iter()
+                    .any(|diff: &KeyDiff| &diff.section == section && &diff.key == key);
+                if value != default && !seen {
+                    diffs.push(KeyDiff {
+                        section: section.clone(),
+                        key: key.clone(),
+                        default,
+                        value,
+                    });
+                }
+            }
+        }
+    }
+
+    // Keys are ordered like in the default config, new ones come last
+    diffs.sort_by_key(|diff| {
+        let key = (diff.section.clone(), diff.key.clone());
+        (
+            default_lines.get(&key).copied().unwrap_or(usize::MAX),
+            lines.get(&key).copied(),
+        )
+    });
+
+    Ok(diffs)
+}
+
+fn read_source(path: &PathBuf) -> Result<String, Vec<ConfigError>> {
+    fs::read_to_string(path).map_err(|e| {
+        vec![ConfigError::Unreadable {
+            path: path.clone(),
+            reason: e.to_string(),
+        }]
+    })
+}
+
+#[cfg(test)]
+pub fn load_default() -> Config {
+    match load_from_str(DEFAULT_CONFIG, &LoadOptions::default()) {
+        Ok(config) => config,
+        Err(errors) => panic!("invalid default config: {:?}", errors),
+    }
+}
+
+fn load_from_str(source: &str, options: &LoadOptions) -> Result<Config, Vec<ConfigError>> {
+    let debug_mode = options.mode == "debug";
+    let base = merge_with_defaults(&parse_ini(source)?);
+    let mut ini = base.clone();
+    let mut locations: HashMap<(String, String), Location> = find_key_lines(source)
+        .into_iter()
+        .map(|(key, line)| (key, Location::Line(line)))
+        .collect();
+    let mut errors = Vec::new();
+    let default = default_ini();
+
+    // Profile keys override the base config, the profile is either given or set for the
+    // mode in the [profiles] section. Errors point to where it was chosen
+    let chosen = match &options.profile {
+        Some(name) => Some((
+            name.clone(),
+            Some(Location::Override("--profile".to_owned())),
+        )),
+        None => [options.mode, "default"].iter().find_map(|mode| {
+            let name = ini.get("profiles", mode).filter(|name| !name.is_empty())?;
+            let location = locations.get(&("profiles".to_owned(), mode.to_string()));
+            Some((name, location.cloned()))
+        }),
+    };
+    let profile = chosen.as_ref().map(|(name, _)| name.clone());
+    let mut profile_overrides = Vec::new();
+    if let Some((name, location)) = chosen {
+        let section = format!("profile.{}", name.to_lowercase());
+        match ini.get_map_ref().get(&section) {
+            Some(keys) => {
+                for (key, value) in keys {
+                    match format!("{}={}", key, value.as_deref().unwrap_or_default())
+                        .parse::<Override>()
+                    {
+                        Ok(o) => {
+                            // Point errors to the profile's line
+                            let location = locations.get(&(section.clone(), key.clone())).cloned();
+                            profile_overrides.push((o, location));
+                        }
+                        Err(_) => errors.push(ConfigError::UnknownKey {
+                            section: section.clone(),
+                            key: key.clone(),
+                            location: locations.get(&(section.clone(), key.clone())).cloned(),
+                        }),
+                    }
+                }
+            }
+            None => errors.push(ConfigError::UnknownProfile { name, location }),
+        }
+    }
+
+    // Then come environment and command-line overrides, one key at a time
+    let overrides: Vec<(Override, Option<Location>)> = profile_overrides
+        .into_iter()
+        .chain(options.overrides.iter().map(|o| {
+            let location = Location::Override(o.origin.clone());
+            (o.clone(), Some(location))
+        }))
+        .collect();
+    for (o, location) in &overrides {
+        ini.set(&o.section, &o.key, Some(o.value.clone()));
+
+        let key = (o.section.clone(), o.key.clone());
+        match location {
+            Some(location) => locations.insert(key, location.clone()),
+            None => locations.remove(&key),
+        };
+    }
+    let overridden = |section: &str, key: &str| {
+        overrides
+            .iter()
+            .any(|(o, _)| o.section == section && o.key == key)
+    };
+
+    // The theme can be chosen by any layer, its colors replace the ones of the config
+    // file, but profiles and overrides still apply on top of it
+    let theme = ini.get("styling", "theme").filter(|name| !name.is_empty());
+    if let Some(name) = &theme {
+        ini = base;
+        match load_theme(name) {
+            Ok(theme) => {
+                apply_layer(&mut ini, &theme);
+                for (section, keys) in theme.get_map_ref() {
+                    for key in keys.keys().filter(|key| !overridden(section, key)) {
+                        locations.remove(&(section.clone(), key.clone()));
+                    }
+                }
+            }
+            Err(reason) => errors.push(ConfigError::InvalidFile {
+                section: "styling".to_owned(),
+                key: "theme".to_owned(),
+                location: locations
+                    .get(&("styling".to_owned(), "theme".to_owned()))
+                    .cloned(),
+                reason,
+            }),
+        }
+
+        for (o, _) in &overrides {
+            ini.set(&o.section, &o.key, Some(o.value.clone()));
+        }
+    }
+
+    for (o, location) in overrides {
+        if !is_known_key(&default, &o.section, &o.key) {
+            errors.push(ConfigError::UnknownKey {
+                section: o.section,
+                key: o.key,
+                location,
+            });
+        }
+    }
+
+    // Alarms given on the command line are added as [alarm.cli_N] sections
+    for (i, time) in options.alarms.iter().enumerate() {
+        let section = format!("alarm.cli_{}", i + 1);
+        for key in ["time", "label"] {
+            ini.set(&section, key, Some(time.clone()));
+            locations.insert(
+                (section.clone(), key.to_owned()),
+                Location::Override("--alarm".to_owned()),
+            );
+        }
+    }
+
+    let mut loader = Loader::new(&ini, locations, errors);
+    let color_support = loader.load_color_support();
+    let be_polite = loader.get("general", "polite");
+    let fps = loader.get_at_least("general", "fps", 1);
+    let color = loader.load_color(debug_mode);
+    let accent = loader.load_optional_color("styling", "accent");
+    let elements = loader.load_elements(&color, accent, debug_mode);
+    // Filling cells would turn them in reverse video without colors
+    let has_colors = color_support != ColorSupport::Monochrome;
+    let background = loader
+        .load_optional_color("styling", "background")
+        .filter(|_| has_colors);
+    let time_format = loader.load_format("format", "time");
+    let date_format = loader.load_format("format", "date");
+    let timezone = loader.load_timezone();
+    let zones = loader.load_zones(&time_format);
+    let alarms = loader.load_alarms();
+    let chimes = loader.load_chimes();
+    let calendar = loader.load_calendar();
+    let frame = Frame {
+        border: loader
+            .parse(
+                "frame",
+                "border",
+                "\"none\", \"single\", \"rounded\", \"double\" or \"ascii\"",
+                Border::from_name,
+            )
+            .unwrap_or(Border::None),
+        padding: loader.get::<u16>("frame", "padding") as i16,
+        fill: loader
+            .load_optional_color("frame", "fill")
+            .filter(|_| has_colors),
+    };
+    let config = Config {
+        profile,
+        theme,
+        be_polite,
+        fps,
+        color,
+        background,
+        color_support,
+        time_format,
+        date_format,
+        timezone,
+        zones,
+        alarms,
+        chimes,
+        calendar,
+        digits: Digits {
+            font: loader.load_font(),
+            scaling: Scaling {
+                min: loader.get("layout", "scale_min"),
+                max: loader.get("layout", "scale_max"),
+                padding: loader.get("layout", "padding"),
+                frame: frame.thickness() as u16,
+            },
+            style: loader.load_style(),
+            shadow: loader
+                .load_optional_color("styling", "shadow")
+                .filter(|_| has_colors),
+        },
+        frame,
+        elements,
+    };
+
+    if loader.errors.is_empty() {
+        Ok(config)
+    } else {
+        // Keys shared by several elements, like the gradient settings, are read once each
+        let mut errors: Vec<ConfigError> = Vec::new();
+        for error in loader.errors {
+            if !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+        Err(errors)
+    }
+}
+
+fn parse_ini(source: &str) -> Result<Ini, Vec<ConfigError>> {
+    let mut ini = Ini::new();
+    ini.read(source.to_owned())
+        .map_err(|reason| vec![ConfigError::Syntax { reason }])?;
+
+    Ok(ini)
+}
+
+fn default_ini() -> Ini {
+    let mut ini = Ini::new();
+    ini.read(DEFAULT_CONFIG.to_owned())
+        .unwrap_or_else(|e| panic!("invalid default config: {}", e));
+
+    ini
+}
+
+// Name of the group an indexed key belongs to, like "gradient_key" for gradient_key_2
+fn indexed_group(section: &str, key: &str) -> Option<&'static str> {
+    let (name, index) = key.rsplit_once('_')?;
+    index.parse::<usize>().ok()?;
+
+    INDEXED_KEYS
+        .iter()
+        .find(|&&(s, n)| s == section && n == name)
+        .map(|&(_, name)| name)
+}
+
+// Use the user config as an override of the default one, so keys added in new versions
+// don't have to be in existing config files. Indexed keys are replaced as a group, so
+// the user can define fewer gradient keys than the default config
+fn merge_with_defaults(user: &Ini) -> Ini {
+    let mut ini = default_ini();
+    apply_layer(&mut ini, user);
+
+    ini
+}
+
+fn apply_layer(ini: &mut Ini, layer: &Ini) {
+    for (section, keys) in layer.get_map_ref() {
+        for key in keys.keys() {
+            if let Some(group) = indexed_group(section, key) {
+                let defaults: Vec<String> = ini
+                    .get_map_ref()
+                    .get(section)
+                    .into_iter()
+                    .flat_map(|keys| keys.keys())
+                    .filter(|&k| indexed_group(section, k) == Some(group))
+                    .filter(|&k| !keys.contains_key(k))
+                    .cloned()
+                    .collect();
+                for k in defaults {
+                    ini.remove_key(section, &k);
+                }
+            }
+        }
+
+        for (key, value) in keys {
+            ini.set(section, key, value.clone());
+        }
+    }
+}
+
+// Names of the bundled themes and of the ones in ~/.config/tlock/themes
+pub fn theme_names() -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_THEMES
+        .iter()
+        .map(|&(name, _)| name.to_owned())
+        .collect();
+
+    let entries = themes_dir().and_then(|dir| fs::read_dir(dir).ok());
+    for entry in entries.into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|e| e == "theme") {
+            if let Some(name) = path.file_stem().and_then(|n| n.to_str()) {
+                names.push(name.to_owned());
+            }
+        }
+    }
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn themes_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("tlock").join("themes"))
+}
+
+// Themes are looked up by name in ~/.config/tlock/themes, then in the bundled ones,
+// unless a path is given
+fn load_theme(name: &str) -> Result<Ini, String> {
+    let path = if name.contains('/') {
+        Some(PathBuf::from(name))
+    } else {
+        themes_dir()
+            .map(|dir| dir.join(format!("{}.theme", name)))
+            .filter(|path| path.exists())
+    };
+
+    let source = match path {
+        Some(path) => fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read theme file {:?}: {}", path, e))?,
+        None => BUILTIN_THEMES
+            .iter()
+            .find(|&&(n, _)| n == name)
+            .map(|&(_, source)| source.to_owned())
+            .ok_or_else(|| format!("Unknown theme '{}'", name))?,
+    };
+
     let mut ini = Ini::new();
-    ini.load(
-        &path
-            .to_str()
-            .unwrap_or_else(|| eprintln_quit!("Invalid configuration path")),
-    )
-    .unwrap_or_else(|_| eprintln_quit!("Unable to parse configuration file"));
+    ini.read(source.clone())
+        .map_err(|e| format!("Unable to parse theme '{}': {}", name, e))?;
+
+    let default = default_ini();
+    let lines = find_key_lines(&source);
+    for (section, keys) in ini.get_map_ref() {
+        for key in keys.keys() {
+            let allowed = THEME_KEYS.contains(&(section.as_str(), key.as_str()))
+                || (["gradient", "elements"].contains(&section.as_str())
+                    && is_known_key(&default, section, key));
+            if !allowed {
+                let line = lines
+                    .get(&(section.clone(), key.clone()))
+                    .map(|line| format!("line {}: ", line))
+                    .unwrap_or_default();
+                return Err(format!(
+                    "{}Key {}.{} can't be set by theme '{}'",
+                    line, section, key, name
+                ));
+            }
+        }
+    }
+
+    Ok(ini)
+}
+
+// Watches the config file, polling its modification time
+pub struct ConfigWatcher {
+    path: PathBuf,
+    options: LoadOptions,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new(path: PathBuf, options: LoadOptions) -> ConfigWatcher {
+        ConfigWatcher {
+            modified: modified_time(&path),
+            path,
+            options,
+            last_check: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    // Reload the config if the file changed since the last call
+    pub fn poll(&mut self) -> Option<Result<Config, Vec<ConfigError>>> {
+        if self.last_check.elapsed() < Self::POLL_INTERVAL {
+            return None;
+        }
+        self.last_check = Instant::now();
+
+        // Editors may replace the file, it can be missing for a short time
+        let modified = modified_time(&self.path)?;
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
 
-    Config {
-        be_polite: get_ini_value(&ini, "general", "polite"),
-        fps: get_ini_value(&ini, "general", "fps"),
-        color: load_color(&ini, debug_mode),
-        time_format: get_ini_value(&ini, "format", "time"),
-        date_format: get_ini_value(&ini, "format", "date"),
+        Some(load_from_file(self.path.clone(), &self.options))
     }
 }
 
-pub fn write_default_config(path: PathBuf) -> () {
+fn modified_time(path: &PathBuf) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+pub fn write_default_config(path: PathBuf) {
     // Write default config file to target path
     let parent = path
         .parent()
@@ -46,103 +852,1254 @@ pub fn write_default_config(path: PathBuf) -> () {
     let _ = fs::write(path, DEFAULT_CONFIG);
 }
 
-fn get_ini_value<T: std::str::FromStr>(ini: &Ini, section: &str, key: &str) -> T {
-    if let Some(value) = ini.get(section, key) {
-        value.parse::<T>().unwrap_or_else(|_| {
-            eprintln_quit!(
-                "Invalid value at {}.{}: Expected {}, got '{}'",
-                section,
-                key,
-                type_name::<T>(),
-                value
+// Reads values from the ini, collecting every error instead of stopping at the first one.
+// Invalid values are replaced by placeholders, the config is rejected anyway
+struct Loader<'a> {
+    ini: &'a Ini,
+    locations: HashMap<(String, String), Location>,
+    errors: Vec<ConfigError>,
+    // Palettes are only read once they are used
+    palettes: HashMap<PaletteSource, Result<Palette, String>>,
+}
+
+impl<'a> Loader<'a> {
+    fn new(
+        ini: &'a Ini,
+        locations: HashMap<(String, String), Location>,
+        errors: Vec<ConfigError>,
+    ) -> Loader<'a> {
+        Loader {
+            ini,
+            locations,
+            errors,
+            palettes: HashMap::new(),
+        }
+    }
+
+    fn location(&self, section: &str, key: &str) -> Option<Location> {
+        self.locations
+            .get(&(section.to_owned(), key.to_owned()))
+            .cloned()
+    }
+
+    // Indices of the keys of an indexed group, in order. They can start anywhere and skip
+    // some numbers
+    fn indices(&self, section: &str, group: &str) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .ini
+            .get_map_ref()
+            .get(section)
+            .into_iter()
+            .flatten()
+            .filter(|(_, value)| value.is_some())
+            .filter_map(|(key, _)| {
+                let (name, index) = key.rsplit_once('_')?;
+                index.parse().ok().filter(|_| name == group)
+            })
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    fn get<T: FromStr + Default>(&mut self, section: &str, key: &str) -> T {
+        self.parse(section, key, type_name::<T>(), |value| value.parse().ok())
+            .unwrap_or_default()
+    }
+
+    fn get_at_least<T: FromStr + Default + PartialOrd + fmt::Display>(
+        &mut self,
+        section: &str,
+        key: &str,
+        min: T,
+    ) -> T {
+        let expected = format!("{} >= {}", type_name::<T>(), min);
+        self.parse(section, key, &expected, |value| {
+            value.parse().ok().filter(|value| *value >= min)
+        })
+        .unwrap_or_default()
+    }
+
+    // Parse a value with a custom parser, `expected` describes the valid values
+    fn parse<T>(
+        &mut self,
+        section: &str,
+        key: &str,
+        expected: &str,
+        parse: impl FnOnce(&str) -> Option<T>,
+    ) -> Option<T> {
+        let Some(value) = self.ini.get(section, key) else {
+            self.errors.push(ConfigError::MissingKey {
+                section: section.to_owned(),
+                key: key.to_owned(),
+            });
+            return None;
+        };
+
+        let parsed = parse(&value);
+        if parsed.is_none() {
+            self.invalid_value(section, key, expected, value);
+        }
+
+        parsed
+    }
+
+    fn invalid_value(&mut self, section: &str, key: &str, expected: &str, value: String) {
+        self.errors.push(ConfigError::InvalidValue {
+            section: section.to_owned(),
+            key: key.to_owned(),
+            location: self.location(section, key),
+            expected: expected.to_owned(),
+            value,
+        });
+    }
+
+    fn invalid_file(&mut self, section: &str, key: &str, reason: String) {
+        self.errors.push(ConfigError::InvalidFile {
+            section: section.to_owned(),
+            key: key.to_owned(),
+            location: self.location(section, key),
+            reason,
+        });
+    }
+
+    fn load_color(&mut self, debug_mode: bool) -> ComputableColor {
+        let color_mode = self.parse(
+            "styling",
+            "color_mode",
+            "\"term\", \"hex\", \"ansi\", \"wal\", \"xresources\", \"base16\" or \"gradient\"",
+            |value| {
+                let modes = ["term", "hex", "ansi", "gradient"];
+                (modes.contains(&value) || PaletteSource::from_name(value).is_some())
+                    .then(|| value.to_owned())
+            },
+        );
+
+        let color = match color_mode.as_deref() {
+            Some("term") => self.parse("styling", "color_term", "0-15", load_term_color),
+            Some("hex") => self.parse("styling", "color_hex", "hex color", load_hex_color),
+            Some("ansi") => self.parse("styling", "color_ansi", "0-255", load_ansi_color),
+            Some("gradient") => return self.load_gradient(debug_mode),
+            // Palette slot, like color_wal=4
+            Some(mode) => {
+                let key = format!("color_{}", mode);
+                let slot = self.parse("styling", &key, "0-15", |value| Some(value.to_owned()));
+                slot.and_then(|slot| self.palette_color("styling", &key, mode, &slot))
+                    .map(|(r, g, b)| Color::Rgb { r, g, b })
+            }
+            None => None,
+        };
+
+        ComputableColor::from(color.unwrap_or(Color::Reset))
+    }
+
+    // Hex color, or a palette slot written as SOURCE:SLOT, like wal:4
+    fn load_color_value(&mut self, section: &str, key: &str) -> Option<(u8, u8, u8)> {
+        let value = self.parse(section, key, "", |value| Some(value.to_owned()))?;
+        self.color_value(section, key, &value)
+    }
+
+    fn color_value(&mut self, section: &str, key: &str, value: &str) -> Option<(u8, u8, u8)> {
+        match value.split_once(':') {
+            Some((source, slot)) => self.palette_color(section, key, source, slot),
+            None => {
+                let color = parse_hex_color(value);
+                if color.is_none() {
+                    let value = self.ini.get(section, key).unwrap_or_default();
+                    self.invalid_value(section, key, "hex color or SOURCE:SLOT", value);
+                }
+                color
+            }
+        }
+    }
+
+    // Color value, or nothing
+    fn load_optional_color(&mut self, section: &str, key: &str) -> Option<Color> {
+        if self.ini.get(section, key)?.is_empty() {
+            return None;
+        }
+
+        let (r, g, b) = self.load_color_value(section, key)?;
+        Some(Color::Rgb { r, g, b })
+    }
+
+    fn palette_color(
+        &mut self,
+        section: &str,
+        key: &str,
+        source: &str,
+        slot: &str,
+    ) -> Option<(u8, u8, u8)> {
+        let value = self.ini.get(section, key).unwrap_or_default();
+        let Some(source) = PaletteSource::from_name(source) else {
+            let expected = "palette \"wal\", \"xresources\" or \"base16\"";
+            self.invalid_value(section, key, expected, value);
+            return None;
+        };
+        let Some(slot) = slot.trim().parse::<usize>().ok().filter(|&slot| slot < 16) else {
+            self.invalid_value(section, key, "palette slot 0-15", value);
+            return None;
+        };
+
+        let color = match self.palette(source) {
+            Ok(palette) => palette.get(slot).ok_or_else(|| {
+                format!(
+                    "Slot {} is missing from the {} palette",
+                    slot,
+                    source.name()
+                )
+            }),
+            Err(reason) => Err(reason.clone()),
+        };
+
+        color
+            .map_err(|reason| self.invalid_file(section, key, reason))
+            .ok()
+    }
+
+    fn palette(&mut self, source: PaletteSource) -> &Result<Palette, String> {
+        if !self.palettes.contains_key(&source) {
+            // Palette files can be set in the [palette] section, pywal and Xresources ones
+            // have a usual location
+            let file = self
+                .ini
+                .get("palette", &format!("{}_file", source.name()))
+                .unwrap_or_default();
+            let path = if let Some(path) = file.strip_prefix("~/") {
+                home_dir().map(|home| home.join(path))
+            } else if !file.is_empty() {
+                Some(PathBuf::from(file))
+            } else {
+                match source {
+                    PaletteSource::Wal => {
+                        cache_dir().map(|dir| dir.join("wal").join("colors.json"))
+                    }
+                    PaletteSource::Xresources => home_dir().map(|home| home.join(".Xresources")),
+                    PaletteSource::Base16 => None,
+                }
+            };
+
+            let palette = match path {
+                Some(path) => Palette::load(source, &path),
+                None => Err(format!("palette.{}_file isn't set", source.name())),
+            };
+            self.palettes.insert(source, palette);
+        }
+
+        &self.palettes[&source]
+    }
+
+    fn load_color_support(&mut self) -> ColorSupport {
+        self.parse(
+            "styling",
+            "color_support",
+            "\"auto\", \"truecolor\", \"256\", \"16\" or \"none\"",
+            ColorSupport::from_name,
+        )
+        .unwrap_or(ColorSupport::TrueColor)
+    }
+
+    fn load_gradient(&mut self, debug_mode: bool) -> ComputableColor {
+        let mut keys = Vec::new();
+
+        // Iterate over all gradient keys, in the order of their index. They are defined
+        // like that in the config file:
+        //   gradient_key_1=...
+        //   gradient_key_2=...
+        //   gradient_key_N=...
+        let indices = self.indices("gradient", "gradient_key");
+        for &i in &indices {
+            let key = format!("gradient_key_{}", i);
+            if let Some(color) = self.load_color_value("gradient", &key) {
+                keys.push((i, color));
+            }
+        }
+        if indices.is_empty() {
+            self.errors.push(ConfigError::MissingKey {
+                section: "gradient".to_owned(),
+                key: "gradient_key_0".to_owned(),
+            });
+        }
+
+        // Segment N, between key N and the next one, can have its own easing:
+        //   gradient_easing_N=...
+        let expected = "\"linear\", \"ease-in\", \"ease-out\" or \"ease-in-out\"";
+        let default_easing = self
+            .parse("gradient", "gradient_easing", expected, Easing::from_name)
+            .unwrap_or(Easing::Linear);
+        let mut easings = Vec::new();
+        for &(i, _) in &keys[..keys.len().saturating_sub(1)] {
+            let key = format!("gradient_easing_{}", i);
+            let easing = if self.ini.get("gradient", &key).is_some() {
+                self.parse("gradient", &key, expected, Easing::from_name)
+                    .unwrap_or(Easing::Linear)
+            } else {
+                default_easing
+            };
+            easings.push(easing);
+        }
+
+        let keys = keys.into_iter().map(|(_, color)| color).collect();
+        self.build_gradient(keys, easings, debug_mode)
+    }
+
+    // Gradient through the keys, using the settings of the [gradient] section
+    fn build_gradient(
+        &mut self,
+        mut keys: Vec<(u8, u8, u8)>,
+        mut easings: Vec<Easing>,
+        debug_mode: bool,
+    ) -> ComputableColor {
+        // Generate gradient loop if needed, going back through the segments
+        let gradient_loop: bool = self.get("gradient", "gradient_loop");
+        if !debug_mode && gradient_loop {
+            for &key in keys.clone().iter().rev().skip(1) {
+                keys.push(key);
+            }
+            for &easing in easings.clone().iter().rev() {
+                easings.push(easing.reversed());
+            }
+        }
+
+        let space = self
+            .parse(
+                "gradient",
+                "gradient_space",
+                "\"srgb\", \"linear\", \"hsl\", \"hsv\", \"oklab\" or \"oklch\"",
+                ColorSpace::from_name,
             )
+            .unwrap_or(ColorSpace::Srgb);
+
+        // I use half characters for debug mode rendering, so we take display size * 2
+        let steps: usize = if debug_mode {
+            debug::DEBUG_COLOR_DISPLAY_SIZE * 2
+        } else {
+            self.get_at_least("gradient", "gradient_steps", 1)
+        };
+        let direction = self
+            .parse(
+                "gradient",
+                "gradient_direction",
+                "\"none\", \"horizontal\", \"vertical\", \"diagonal\" or \"radial\"",
+                GradientDirection::from_name,
+            )
+            .unwrap_or(GradientDirection::None);
+        let scroll = self.get("gradient", "gradient_scroll");
+
+        if keys.is_empty() {
+            return ComputableColor::from(Color::Reset);
+        }
+
+        generate_gradient(keys, easings, space, steps).with_direction(direction, scroll)
+    }
+
+    fn load_elements(
+        &mut self,
+        color: &ComputableColor,
+        accent: Option<Color>,
+        debug_mode: bool,
+    ) -> Elements {
+        // The colon inherits the digits' color, secondary text inherits the accent color
+        let digits = self.load_element("digits", color.clone(), debug_mode);
+        let secondary = accent
+            .map(ComputableColor::from)
+            .unwrap_or_else(|| digits.color.clone());
+
+        Elements {
+            colon: self.load_element("colon", digits.color.clone(), debug_mode),
+            date: self.load_element("date", secondary.clone(), debug_mode),
+            status: self.load_element("status", secondary.clone(), debug_mode),
+            laps: self.load_element("laps", secondary.clone(), debug_mode),
+            header: self.load_element("header", secondary.clone(), debug_mode),
+            border: self.load_element("border", secondary, debug_mode),
+            highlight: self.load_element("highlight", digits.color.clone(), debug_mode),
+            chime: self.load_element("chime", digits.color.clone(), debug_mode),
+            digits,
+        }
+    }
+
+    fn load_element(
+        &mut self,
+        name: &str,
+        inherited: ComputableColor,
+        debug_mode: bool,
+    ) -> TextStyle {
+        let key = format!("{}_color", name);
+        let value: String = self.get("elements", &key);
+        let color = match value.as_str() {
+            "" => Some(inherited),
+            "gradient" => Some(self.load_gradient(debug_mode)),
+            // One color, or a gradient through several ones
+            value => {
+                let keys: Option<Vec<_>> = value
+                    .split(',')
+                    .map(|color| self.color_value("elements", &key, color.trim()))
+                    .collect();
+                keys.map(|keys| match keys[..] {
+                    [(r, g, b)] => ComputableColor::from(Color::Rgb { r, g, b }),
+                    _ => {
+                        let easing = self
+                            .parse(
+                                "gradient",
+                                "gradient_easing",
+                                "\"linear\", \"ease-in\", \"ease-out\" or \"ease-in-out\"",
+                                Easing::from_name,
+                            )
+                            .unwrap_or(Easing::Linear);
+                        let easings = vec![easing; keys.len() - 1];
+                        self.build_gradient(keys, easings, debug_mode)
+                    }
+                })
+            }
+        };
+
+        let attributes = self
+            .parse(
+                "elements",
+                &format!("{}_attributes", name),
+                "comma-separated \"bold\", \"italic\", \"dim\" or \"underline\"",
+                load_attributes,
+            )
+            .unwrap_or_default();
+
+        TextStyle {
+            color: color.unwrap_or(ComputableColor::from(Color::Reset)),
+            attributes,
+        }
+    }
+
+    fn load_format(&mut self, section: &str, key: &str) -> String {
+        self.parse(section, key, "strftime format", |value| {
+            let valid = StrftimeItems::new(value).all(|item| item != Item::Error);
+            valid.then(|| value.to_owned())
+        })
+        .unwrap_or_default()
+    }
+
+    fn load_timezone(&mut self) -> Option<Tz> {
+        let name: String = self.get("general", "timezone");
+        if name.is_empty() {
+            return system_timezone();
+        }
+
+        self.parse(
+            "general",
+            "timezone",
+            "IANA time zone, like Europe/Paris, or nothing",
+            |value| value.parse::<Tz>().ok(),
+        )
+    }
+
+    fn load_zones(&mut self, time_format: &str) -> Vec<Zone> {
+        let mut zones = Vec::new();
+
+        // Zones are defined like gradient keys, each one can have a label and a format:
+        //   zone_N=Europe/Paris
+        //   zone_label_N=Paris
+        //   zone_format_N=%H:%M
+        let indices = self.indices("world", "zone");
+        for &i in &indices {
+            let tz = self.parse(
+                "world",
+                &format!("zone_{}", i),
+                "IANA time zone, like Europe/Paris",
+                |value| value.parse::<Tz>().ok(),
+            );
+
+            let label = self
+                .ini
+                .get("world", &format!("zone_label_{}", i))
+                .filter(|label| !label.is_empty());
+            let format_key = format!("zone_format_{}", i);
+            let format = match self.ini.get("world", &format_key) {
+                Some(format) if !format.is_empty() => self.load_format("world", &format_key),
+                _ => time_format.to_owned(),
+            };
+
+            if let Some(tz) = tz {
+                zones.push(Zone {
+                    tz,
+                    label: label.unwrap_or_else(|| Zone::default_label(tz)),
+                    format,
+                });
+            }
+        }
+        if indices.is_empty() {
+            self.errors.push(ConfigError::MissingKey {
+                section: "world".to_owned(),
+                key: "zone_0".to_owned(),
+            });
+        }
+
+        zones
+    }
+
+    fn load_alarms(&mut self) -> Vec<Alarm> {
+        let mut sections: Vec<String> = self
+            .ini
+            .sections()
+            .into_iter()
+            .filter(|section| section.starts_with("alarm."))
+            .collect();
+        sections.sort();
+
+        sections
+            .iter()
+            .filter_map(|section| self.load_alarm(section))
+            .collect()
+    }
+
+    fn load_alarm(&mut self, section: &str) -> Option<Alarm> {
+        let time = self.parse(section, "time", "time, like 07:30", alarm::parse_time);
+        // Alarms without days go off every day
+        let days = match self.ini.get(section, "days") {
+            Some(_) => self.parse(
+                section,
+                "days",
+                "days, like mon-fri or sat,sun",
+                alarm::parse_days,
+            ),
+            None => Some([true; 7]),
+        };
+        let label = self
+            .ini
+            .get(section, "label")
+            .filter(|label| !label.is_empty())
+            .unwrap_or_else(|| section.trim_start_matches("alarm.").to_owned());
+
+        // Other keys default to the [alarms] ones
+        let bell = self.get(self.alarm_section(section, "bell"), "bell");
+        let flash = self.get(self.alarm_section(section, "flash"), "flash");
+        let command: String = self.get(self.alarm_section(section, "command"), "command");
+        let snooze: i64 = self.get_at_least(self.alarm_section(section, "snooze"), "snooze", 1);
+
+        Some(Alarm {
+            time: time?,
+            days: days?,
+            label,
+            bell,
+            flash,
+            command: Some(command).filter(|command| !command.is_empty()),
+            snooze: TimeDelta::minutes(snooze),
         })
-    } else {
-        eprintln_quit!("Missing required config key: {}.{}", section, key)
     }
-}
 
-fn load_color(ini: &Ini, debug_mode: bool) -> ComputableColor {
-    let color_mode: String = get_ini_value(&ini, "styling", "color_mode");
+    fn load_chimes(&mut self) -> Chimes {
+        let command: String = self.get("chimes", "command");
+        let sound: String = self.get("chimes", "sound");
+        let player: String = self.get("chimes", "player");
 
-    match color_mode.as_str() {
-        "term" => {
-            let color: u8 = get_ini_value(&ini, "styling", "color_term");
-            ComputableColor::from(load_term_color(color))
+        Chimes {
+            interval: self
+                .parse(
+                    "chimes",
+                    "every",
+                    "\"none\", \"hour\" or \"quarter\"",
+                    ChimeInterval::from_name,
+                )
+                .unwrap_or(ChimeInterval::None),
+            bell: self.get("chimes", "bell"),
+            flash: self.get("chimes", "flash"),
+            command: Some(command).filter(|command| !command.is_empty()),
+            sound: Some(sound).filter(|sound| !sound.is_empty()),
+            player: match player.as_str() {
+                "" => alarm::default_player().to_owned(),
+                _ => player,
+            },
         }
-        "hex" => {
-            let color: String = get_ini_value(&ini, "styling", "color_hex");
-            ComputableColor::from(load_hex_color(&color))
+    }
+
+    fn load_calendar(&mut self) -> Calendar {
+        Calendar {
+            placement: self
+                .parse(
+                    "calendar",
+                    "position",
+                    "\"none\", \"below\" or \"beside\"",
+                    Placement::from_name,
+                )
+                .unwrap_or(Placement::None),
+            first_weekday: self
+                .parse(
+                    "calendar",
+                    "first_weekday",
+                    "day of the week, like monday or sun",
+                    |value| value.parse::<Weekday>().ok(),
+                )
+                .unwrap_or(Weekday::Mon),
+            week_numbers: self.get("calendar", "week_numbers"),
+            previous: self.get("calendar", "previous"),
+            next: self.get("calendar", "next"),
         }
-        "ansi" => {
-            let color: u8 = get_ini_value(&ini, "styling", "color_ansi");
-            ComputableColor::from(load_ansi_color(color))
+    }
+
+    // Section a key of an alarm is read from, the [alarms] one if the alarm doesn't set it
+    fn alarm_section<'s>(&self, section: &'s str, key: &str) -> &'s str {
+        if self.ini.get(section, key).is_some() {
+            section
+        } else {
+            "alarms"
         }
-        "gradient" => load_gradient(ini, debug_mode),
-        _ => eprintln_quit!("Invalid color mode: {}", color_mode),
     }
+
+    fn load_style(&mut self) -> Style {
+        self.parse(
+            "styling",
+            "style",
+            "\"block\", \"half\", \"quadrant\" or \"braille\"",
+            Style::from_name,
+        )
+        .unwrap_or(Style::Block)
+    }
+
+    fn load_font(&mut self) -> Font {
+        let name: String = self.get("styling", "font");
+        if name == "default" || name.is_empty() {
+            return Font::builtin();
+        }
+
+        // Fonts are looked up by name in ~/.config/tlock/fonts, unless a path is given
+        let path = if name.contains('/') {
+            Some(PathBuf::from(name))
+        } else {
+            config_dir().map(|dir| {
+                dir.join("tlock")
+                    .join("fonts")
+                    .join(format!("{}.font", name))
+            })
+        };
+
+        let font = match path {
+            Some(path) => Font::load(&path),
+            None => Err("Unable to get configuration directory".to_owned()),
+        };
+
+        font.unwrap_or_else(|reason| {
+            self.errors.push(ConfigError::InvalidFile {
+                section: "styling".to_owned(),
+                key: "font".to_owned(),
+                location: self.location("styling", "font"),
+                reason,
+            });
+            Font::builtin()
+        })
+    }
+}
+
+// Zone of the system, from TZ or the system settings. TZ can also be a POSIX rule, only
+// understood by the system zone
+fn system_timezone() -> Option<Tz> {
+    env::var("TZ")
+        .map(|name| name.trim_start_matches(':').to_owned())
+        .or_else(|_| iana_time_zone::get_timezone())
+        .ok()
+        .and_then(|name| name.parse().ok())
+}
+
+fn load_term_color(value: &str) -> Option<Color> {
+    let index: usize = value.parse().ok()?;
+    ANSI_COLORS.get(index).map(|&(color, _)| color)
 }
 
-fn load_term_color(value: u8) -> Color {
-    match value {
-        0 => Color::Black,
-        1 => Color::DarkRed,
-        2 => Color::DarkGreen,
-        3 => Color::DarkYellow,
-        4 => Color::DarkBlue,
-        5 => Color::DarkMagenta,
-        6 => Color::DarkCyan,
-        7 => Color::Grey,
-        8 => Color::DarkGrey,
-        9 => Color::Red,
-        10 => Color::Green,
-        11 => Color::Yellow,
-        12 => Color::Blue,
-        13 => Color::Magenta,
-        14 => Color::Cyan,
-        15 => Color::White,
-        _ => eprintln_quit!("Invalid terminal color: {}", value),
+fn load_hex_color(value: &str) -> Option<Color> {
+    let (r, g, b) = parse_hex_color(value)?;
+    Some(Color::Rgb { r, g, b })
+}
+
+fn load_ansi_color(value: &str) -> Option<Color> {
+    value.parse().ok().map(Color::AnsiValue)
+}
+
+fn load_attributes(value: &str) -> Option<Attributes> {
+    let mut attributes = Attributes::default();
+    for name in value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        attributes.set(match name {
+            "bold" => Attribute::Bold,
+            "italic" => Attribute::Italic,
+            "dim" => Attribute::Dim,
+            "underline" => Attribute::Underlined,
+            _ => return None,
+        });
     }
+
+    Some(attributes)
 }
 
-fn load_hex_color(value: &str) -> Color {
-    let rgb = parse_hex_color(value);
-    Color::Rgb {
-        r: rgb.0,
-        g: rgb.1,
-        b: rgb.2,
+fn is_known_key(default: &Ini, section: &str, key: &str) -> bool {
+    let known = default
+        .get_map_ref()
+        .get(section)
+        .is_some_and(|keys| keys.contains_key(key));
+
+    known || indexed_group(section, key).is_some()
+}
+
+fn find_unknown_keys(ini: &Ini, source: &str) -> Vec<ConfigError> {
+    let default = default_ini();
+
+    let lines = find_key_lines(source);
+    let location = |section: &str, key: &str| {
+        lines
+            .get(&(section.to_owned(), key.to_owned()))
+            .map(|line| Location::Line(*line))
+    };
+
+    let mut errors = Vec::new();
+    for (section, keys) in ini.get_map_ref() {
+        // Profile keys are checked when loading each profile
+        if section.starts_with("profile.") {
+            continue;
+        }
+
+        // Alarms can set the keys of the [alarms] section
+        if section.starts_with("alarm.") {
+            for key in keys.keys() {
+                if !ALARM_KEYS.contains(&key.as_str()) && !is_known_key(&default, "alarms", key) {
+                    errors.push(ConfigError::UnknownKey {
+                        section: section.clone(),
+                        key: key.clone(),
+                        location: location(section, key),
+                    });
+                }
+            }
+            continue;
+        }
+
+        if !default.get_map_ref().contains_key(section) {
+            errors.push(ConfigError::UnknownSection {
+                section: section.clone(),
+                location: location(section, ""),
+            });
+            continue;
+        }
+
+        for key in keys.keys() {
+            if !is_known_key(&default, section, key) {
+                errors.push(ConfigError::UnknownKey {
+                    section: section.clone(),
+                    key: key.clone(),
+                    location: location(section, key),
+                });
+            }
+        }
+    }
+
+    // Profiles chosen for modes must exist
+    if let Some(modes) = ini.get_map_ref().get("profiles") {
+        for (mode, name) in modes {
+            let Some(name) = name.as_ref().filter(|name| !name.is_empty()) else {
+                continue;
+            };
+            if !ini
+                .get_map_ref()
+                .contains_key(&format!("profile.{}", name.to_lowercase()))
+            {
+                errors.push(ConfigError::UnknownProfile {
+                    name: name.clone(),
+                    location: location("profiles", mode),
+                });
+            }
+        }
     }
+
+    // Report in file order
+    errors.sort_by_key(|error| match error {
+        ConfigError::UnknownSection { location, .. }
+        | ConfigError::UnknownKey { location, .. }
+        | ConfigError::UnknownProfile { location, .. } => match location {
+            Some(Location::Line(line)) => Some(*line),
+            _ => None,
+        },
+        _ => None,
+    });
+
+    errors
 }
 
-fn load_ansi_color(value: u8) -> Color {
-    Color::AnsiValue(value)
+// Line number of each key, sections and keys are lowercased like the ini parser does.
+// Sections are stored with an empty key
+fn find_key_lines(source: &str) -> HashMap<(String, String), usize> {
+    let mut lines = HashMap::new();
+    let mut section = String::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_lowercase();
+            lines.insert((section.clone(), String::new()), i + 1);
+        } else if let Some((key, _)) = line.split_once(['=', ':']) {
+            lines.insert((section.clone(), key.trim().to_lowercase()), i + 1);
+        }
+    }
+
+    lines
 }
 
-fn load_gradient(ini: &Ini, debug_mode: bool) -> ComputableColor {
-    let mut keys = Vec::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Iterate over all gradient keys, they are defined like that in the config file:
-    //   gradient_key_1=...
-    //   gradient_key_2=...
-    //   gradient_key_N=...
-    let mut i = 0;
-    while let Some(key) = ini.get("gradient", &format!("gradient_key_{}", i)) {
-        keys.push(parse_hex_color(&key));
-        i += 1;
+    #[test]
+    fn all_errors_are_collected() {
+        let source =
+            "[general]\nfps=fast\n\n[styling]\ncolor_mode=hex\ncolor_hex=e6ecfg\nstyle=round\n";
+
+        let errors = match load_from_str(source, &LoadOptions::default()) {
+            Ok(_) => panic!("config should be invalid"),
+            Err(errors) => errors,
+        };
+        assert_eq!(
+            errors,
+            vec![
+                ConfigError::InvalidValue {
+                    section: "general".to_owned(),
+                    key: "fps".to_owned(),
+                    location: Some(Location::Line(2)),
+                    expected: "u64 >= 1".to_owned(),
+                    value: "fast".to_owned(),
+                },
+                ConfigError::InvalidValue {
+                    section: "styling".to_owned(),
+                    key: "color_hex".to_owned(),
+                    location: Some(Location::Line(6)),
+                    expected: "hex color".to_owned(),
+                    value: "e6ecfg".to_owned(),
+                },
+                ConfigError::InvalidValue {
+                    section: "styling".to_owned(),
+                    key: "style".to_owned(),
+                    location: Some(Location::Line(7)),
+                    expected: "\"block\", \"half\", \"quadrant\" or \"braille\"".to_owned(),
+                    value: "round".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "line 2: Invalid value at general.fps: Expected u64 >= 1, got 'fast'"
+        );
+    }
+
+    // Write the file with a new modification time, and let the watcher poll right away
+    fn touch(watcher: &mut ConfigWatcher, source: &str, modified: SystemTime) {
+        fs::write(&watcher.path, source).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&watcher.path)
+            .and_then(|file| file.set_modified(modified))
+            .unwrap();
+        watcher.last_check = Instant::now()
+            .checked_sub(ConfigWatcher::POLL_INTERVAL)
+            .unwrap();
     }
 
-    // Generate gradient loop if needed
-    if !debug_mode && get_ini_value(&ini, "gradient", "gradient_loop") {
-        for &key in keys.clone().iter().rev().skip(1) {
-            keys.push(key);
+    #[test]
+    fn watcher_reloads_changed_files() {
+        let path = std::env::temp_dir().join("tlock_watcher_test");
+        fs::write(&path, "[general]\nfps=30\n").unwrap();
+        let mut watcher = ConfigWatcher::new(path, LoadOptions::default());
+        let start = SystemTime::now();
+
+        touch(
+            &mut watcher,
+            "[general]\nfps=60\n",
+            start + Duration::from_secs(1),
+        );
+        let mut config = match watcher.poll() {
+            Some(Ok(config)) => config,
+            _ => panic!("changed file should be reloaded"),
+        };
+        assert_eq!(config.fps, 60);
+
+        // Invalid files are reported once, the running config stays
+        touch(
+            &mut watcher,
+            "[general]\nfps=fast\n",
+            start + Duration::from_secs(2),
+        );
+        match watcher.poll() {
+            Some(Err(errors)) => assert!(matches!(
+                errors.as_slice(),
+                [ConfigError::InvalidValue { key, .. }] if key == "fps"
+            )),
+            _ => panic!("invalid file should be reported"),
         }
+        watcher.last_check = Instant::now()
+            .checked_sub(ConfigWatcher::POLL_INTERVAL)
+            .unwrap();
+        assert!(watcher.poll().is_none());
+        assert_eq!(config.fps, 60);
+
+        touch(
+            &mut watcher,
+            "[general]\nfps=10\n",
+            start + Duration::from_secs(3),
+        );
+        if let Some(Ok(new_config)) = watcher.poll() {
+            config = new_config;
+        }
+        assert_eq!(config.fps, 10);
     }
 
-    // I use half characters for debug mode rendering, so we take display size * 2
-    let steps: usize = if debug_mode {
-        debug::DEBUG_COLOR_DISPLAY_SIZE * 2
-    } else {
-        get_ini_value(&ini, "gradient", "gradient_steps")
-    };
-    generate_gradient(keys, steps - 1)
+    #[test]
+    fn watcher_ignores_unchanged_files() {
+        let path = std::env::temp_dir().join("tlock_watcher_unchanged_test");
+        fs::write(&path, "[general]\nfps=30\n").unwrap();
+        let mut watcher = ConfigWatcher::new(path, LoadOptions::default());
+
+        // Neither too early nor without a change
+        assert!(watcher.poll().is_none());
+        watcher.last_check = Instant::now()
+            .checked_sub(ConfigWatcher::POLL_INTERVAL)
+            .unwrap();
+        assert!(watcher.poll().is_none());
+    }
+
+    #[test]
+    fn check_reports_unknown_keys_and_ranges() {
+        let source = "[general]\nfps=0\nfpss=30\n\n[format]\ntime=%H:%Q\n\n[gradient]\ngradient_key_0=ffffff\ngradient_key_1=000000\n\n[extra]\nkey=value\n";
+        let path = std::env::temp_dir().join("tlock_check_test");
+        fs::write(&path, source).unwrap();
+
+        let errors: Vec<String> = match check_file(path) {
+            Ok(_) => panic!("config should be invalid"),
+            Err(errors) => errors.iter().map(|e| e.to_string()).collect(),
+        };
+        assert_eq!(
+            errors,
+            vec![
+                "line 3: Unknown config key: general.fpss",
+                "line 12: Unknown section: [extra]",
+                "line 2: Invalid value at general.fps: Expected u64 >= 1, got '0'",
+                "line 6: Invalid value at format.time: Expected strftime format, got '%H:%Q'",
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_keys_use_defaults() {
+        let source =
+            "[general]\nfps=60\n[gradient]\ngradient_key_0=ffffff\ngradient_key_1=000000\n";
+        let ini = merge_with_defaults(&parse_ini(source).unwrap());
+
+        assert_eq!(ini.get("general", "fps"), Some("60".to_owned()));
+        assert_eq!(ini.get("general", "polite"), Some("true".to_owned()));
+        assert_eq!(
+            ini.get("gradient", "gradient_key_1"),
+            Some("000000".to_owned())
+        );
+        assert_eq!(ini.get("gradient", "gradient_key_2"), None);
+        assert!(load_from_str(source, &LoadOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn indexed_keys_can_start_anywhere() {
+        let source = "[gradient]\ngradient_key_1=ffffff\ngradient_key_3=000000\n\n\
+            [world]\nzone_10=Asia/Tokyo\nzone_label_10=Tokyo\nzone_2=Europe/Paris\n";
+        let config = load_from_str(source, &LoadOptions::default()).unwrap_or_else(|_| panic!());
+
+        // Zones come in the order of their index
+        let zones: Vec<(Tz, &str)> = config
+            .zones
+            .iter()
+            .map(|zone| (zone.tz, zone.label.as_str()))
+            .collect();
+        assert_eq!(
+            zones,
+            vec![(Tz::Europe__Paris, "Paris"), (Tz::Asia__Tokyo, "Tokyo")]
+        );
+    }
+
+    #[test]
+    fn overrides_are_applied_last() {
+        let source = "[general]\nfps=60\n";
+        let overrides = [
+            "general.fps=0".parse::<Override>().unwrap(),
+            "Styling.Color_Mode = hex".parse::<Override>().unwrap(),
+        ];
+        assert!("general.fps".parse::<Override>().is_err());
+        assert!("fps=1".parse::<Override>().is_err());
+
+        // Errors from overrides don't point to the file
+        let options = LoadOptions {
+            overrides: overrides.to_vec(),
+            ..LoadOptions::default()
+        };
+        let errors = match load_from_str(source, &options) {
+            Ok(_) => panic!("config should be invalid"),
+            Err(errors) => errors,
+        };
+        assert_eq!(
+            errors,
+            vec![ConfigError::InvalidValue {
+                section: "general".to_owned(),
+                key: "fps".to_owned(),
+                location: Some(Location::Override("--set".to_owned())),
+                expected: "u64 >= 1".to_owned(),
+                value: "0".to_owned(),
+            }]
+        );
+        assert!(!errors[0].in_file());
+
+        let options = LoadOptions {
+            overrides: vec!["general.fps=5".parse::<Override>().unwrap()],
+            ..LoadOptions::default()
+        };
+        let config = load_from_str(source, &options).unwrap_or_else(|_| panic!());
+        assert_eq!(config.fps, 5);
+    }
+
+    #[test]
+    fn env_variables_are_overrides() {
+        let vars = [
+            ("TLOCK_STYLING_COLOR_HEX", "ff8800"),
+            ("TLOCK_GENERAL_FPS", "60"),
+            ("TLOCK_UNKNOWN_KEY", "value"),
+            ("TLOCK_STYLING", "value"),
+            ("PATH", "/usr/bin"),
+        ]
+        .map(|(name, value)| (name.to_owned(), value.to_owned()));
+
+        // Only the first underscore separates the section from the key
+        let from_var = |value: &str, name: &str| Override {
+            origin: name.to_owned(),
+            ..value.parse().unwrap()
+        };
+        assert_eq!(
+            overrides_from_vars(vars.into_iter()),
+            vec![
+                from_var("general.fps=60", "TLOCK_GENERAL_FPS"),
+                from_var("styling.color_hex=ff8800", "TLOCK_STYLING_COLOR_HEX"),
+            ]
+        );
+    }
+
+    #[test]
+    fn profiles_override_the_base_config() {
+        let source = "[general]\nfps=20\n\n[profiles]\ntimer=focus\n\n[profile.focus]\ngeneral.fps=5\nformat.time=%H\n";
+        let load = |mode, profile: Option<&str>| {
+            let options = LoadOptions {
+                mode,
+                profile: profile.map(str::to_owned),
+                ..LoadOptions::default()
+            };
+            load_from_str(source, &options)
+        };
+
+        let config = load("clock", None).unwrap_or_else(|_| panic!());
+        assert_eq!((config.profile, config.fps), (None, 20));
+
+        let config = load("timer", None).unwrap_or_else(|_| panic!());
+        assert_eq!((config.profile.as_deref(), config.fps), (Some("focus"), 5));
+        assert_eq!(config.time_format, "%H");
+
+        let config = load("clock", Some("focus")).unwrap_or_else(|_| panic!());
+        assert_eq!(config.fps, 5);
+
+        assert!(matches!(
+            load("clock", Some("calm")).err().as_deref(),
+            Some([ConfigError::UnknownProfile { .. }])
+        ));
+
+        // Missing profiles point to the [profiles] key that chose them
+        let options = LoadOptions {
+            mode: "timer",
+            ..LoadOptions::default()
+        };
+        assert_eq!(
+            load_from_str("[profiles]\ntimer=calm\n", &options).err(),
+            Some(vec![ConfigError::UnknownProfile {
+                name: "calm".to_owned(),
+                location: Some(Location::Line(2)),
+            }])
+        );
+    }
+
+    #[test]
+    fn themes_replace_the_file_colors() {
+        let source = "[styling]\ntheme=nord\ncolor_mode=hex\naccent=ffffff\n";
+        let config = load_from_str(source, &LoadOptions::default()).unwrap_or_else(|_| panic!());
+        assert_eq!(config.theme.as_deref(), Some("nord"));
+        assert_eq!(config.color.get_keys_count(), 256);
+        assert_eq!(
+            config.background,
+            Some(Color::Rgb {
+                r: 0x2e,
+                g: 0x34,
+                b: 0x40
+            })
+        );
+
+        // Overrides still apply on top of the theme
+        let options = LoadOptions {
+            overrides: vec!["styling.accent=ffffff".parse().unwrap()],
+            ..LoadOptions::default()
+        };
+        let config = load_from_str(source, &options).unwrap_or_else(|_| panic!());
+        assert_eq!(
+            config.elements.status.color.get_value(),
+            Color::Rgb {
+                r: 255,
+                g: 255,
+                b: 255
+            }
+        );
+
+        for name in BUILTIN_THEMES.map(|(name, _)| name) {
+            assert!(load_theme(name).is_ok(), "{}", name);
+        }
+        assert!(load_theme("unknown").is_err());
+    }
+
+    #[test]
+    fn timezones_are_validated() {
+        let config = load_from_str("[general]\ntimezone=Asia/Tokyo\n", &LoadOptions::default())
+            .unwrap_or_else(|_| panic!());
+        assert_eq!(config.timezone, Some(Tz::Asia__Tokyo));
+
+        // Errors point to the flag the zone was given with
+        let options = LoadOptions {
+            overrides: vec![Override {
+                origin: "--tz".to_owned(),
+                .."general.timezone=Mars/Olympus".parse().unwrap()
+            }],
+            ..LoadOptions::default()
+        };
+        let errors = load_from_str("[general]\ntimezone=UTC\n", &options)
+            .err()
+            .unwrap_or_default();
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "--tz: Invalid value at general.timezone: Expected IANA time zone, like \
+                 Europe/Paris, or nothing, got 'Mars/Olympus'"
+            ]
+        );
+        assert!(!errors[0].in_file());
+    }
+
+    #[test]
+    fn alarm_errors_point_to_the_flag() {
+        let options = LoadOptions {
+            alarms: vec!["25:99".to_owned()],
+            ..LoadOptions::default()
+        };
+        let errors = load_from_str("", &options).err().unwrap_or_default();
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(messages.len(), 1);
+        assert!(
+            messages[0].starts_with("--alarm: Invalid value at alarm.cli_1.time: "),
+            "{}",
+            messages[0]
+        );
+        assert!(!errors[0].in_file());
+    }
+
+    #[test]
+    fn alarms_are_loaded() {
+        let source = "[alarms]\nsnooze=10\n\n\
+            [alarm.wake]\ntime=07:30\ndays=mon-fri\nlabel=Wake up\nbell=false\n";
+        let options = LoadOptions {
+            alarms: vec!["14:00".to_owned()],
+            ..LoadOptions::default()
+        };
+        let config = load_from_str(source, &options).unwrap_or_else(|_| panic!());
+
+        let [cli, wake] = &config.alarms[..] else {
+            panic!("expected 2 alarms, got {:?}", config.alarms);
+        };
+        assert_eq!(wake.label, "Wake up");
+        assert_eq!(wake.days, [true, true, true, true, true, false, false]);
+        assert!(!wake.bell && wake.flash);
+        assert_eq!(wake.snooze, TimeDelta::minutes(10));
+        assert_eq!(cli.label, "14:00");
+        assert_eq!(cli.days, [true; 7]);
+
+        let errors = load_from_str("[alarm.nap]\ntime=25:00\nvolume=11\n", &options)
+            .err()
+            .unwrap_or_default();
+        assert!(errors
+            .iter()
+            .any(|e| matches!(e, ConfigError::InvalidValue { key, .. } if key == "time")));
+
+        let source = "[alarm.nap]\ntime=13:00\nsnooze=10\nvolume=11\n";
+        let ini = parse_ini(source).unwrap_or_else(|_| panic!());
+        assert!(matches!(
+            find_unknown_keys(&ini, source).as_slice(),
+            [ConfigError::UnknownKey { key, .. }] if key == "volume"
+        ));
+    }
+
+    #[test]
+    fn elements_have_their_own_styles() {
+        let source = "[styling]\ncolor_mode=hex\ncolor_hex=ffffff\naccent=ff0000\n\
+            [elements]\ncolon_color=0000ff\nlaps_color=000000,ffffff\n\
+            date_attributes=italic, dim\nstatus_attributes=\n";
+        let config = load_from_str(source, &LoadOptions::default()).unwrap_or_else(|_| panic!());
+        let elements = &config.elements;
+        let rgb = |r, g, b| Color::Rgb { r, g, b };
+
+        assert_eq!(elements.digits.color.get_value(), rgb(255, 255, 255));
+        assert_eq!(elements.colon.color.get_value(), rgb(0, 0, 255));
+        assert_eq!(elements.date.color.get_value(), rgb(255, 0, 0));
+        assert_eq!(elements.laps.color.get_keys_count(), 256);
+        assert_eq!(elements.header.attributes, Attribute::Bold.into());
+        assert_eq!(elements.status.attributes, Attributes::default());
+        assert!(elements.date.attributes.has(Attribute::Italic));
+        assert!(elements.date.attributes.has(Attribute::Dim));
+
+        let source = "[elements]\ndate_attributes=blink\nheader_color=00000g\n";
+        let errors = match load_from_str(source, &LoadOptions::default()) {
+            Ok(_) => panic!("config should be invalid"),
+            Err(errors) => errors,
+        };
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                ConfigError::InvalidValue { key: date, .. },
+                ConfigError::InvalidValue { key: header, .. },
+            ] if date == "date_attributes" && header == "header_color"
+        ));
+    }
+
+    #[test]
+    fn palette_colors_are_resolved() {
+        let path = std::env::temp_dir().join("tlock_palette_test.json");
+        fs::write(&path, r##"{"colors": {"color1": "#ff8800"}}"##).unwrap();
+
+        let source = format!(
+            "[styling]\ncolor_mode=wal\ncolor_wal=1\naccent=wal:1\n[palette]\nwal_file={}\n",
+            path.display()
+        );
+        let config = load_from_str(&source, &LoadOptions::default()).unwrap_or_else(|_| panic!());
+        let orange = Color::Rgb {
+            r: 0xff,
+            g: 0x88,
+            b: 0x00,
+        };
+        assert_eq!(config.color.get_value(), orange);
+        assert_eq!(config.elements.date.color.get_value(), orange);
+
+        // Slot 2 isn't in the palette
+        let source = source.replace("1\n", "2\n");
+        let errors = match load_from_str(&source, &LoadOptions::default()) {
+            Ok(_) => panic!("config should be invalid"),
+            Err(errors) => errors,
+        };
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                ConfigError::InvalidFile { .. },
+                ConfigError::InvalidFile { .. }
+            ]
+        ));
+    }
 }
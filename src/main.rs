@@ -1,18 +1,30 @@
-use core::panic;
 use std::{
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    process, thread,
+    time::{Duration, Instant},
 };
 
 use clap::{Parser, Subcommand};
-use config::write_default_config;
-use crossterm::{cursor, execute, terminal};
+use config::{write_default_config, Config, ConfigError, ConfigWatcher, LoadOptions, Override};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Color},
+    terminal,
+};
 use dirs::config_dir;
 
-use crate::modes::debug;
+use crate::{
+    modes::{debug, Effect, Mode},
+    rendering::{backend::CrosstermBackend, renderer::Renderer, TextStyle},
+};
 
+mod alarm;
 mod config;
 mod modes;
+mod palette;
 mod rendering;
 mod utils;
 
@@ -29,6 +41,21 @@ struct Cli {
     #[arg(short, long, action)]
     yes: bool,
 
+    // Override a config key, on top of the TLOCK_SECTION_KEY environment variables
+    #[arg(long = "set", value_name = "SECTION.KEY=VALUE")]
+    overrides: Vec<Override>,
+
+    // Use a [profile.NAME] section of the config, instead of the mode's default one
+    #[arg(short, long, value_name = "NAME")]
+    profile: Option<String>,
+
+    // Show the time in another zone, instead of the [general] timezone one
+    #[arg(long, value_name = "ZONE", conflicts_with = "utc")]
+    tz: Option<String>,
+
+    #[arg(long, action)]
+    utc: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -38,6 +65,12 @@ enum Commands {
     #[clap(alias = "d")]
     Debug {},
 
+    Clock {
+        // Alarm going off at the given time, on top of the [alarm.NAME] ones
+        #[arg(long, value_name = "HH:MM")]
+        alarm: Vec<String>,
+    },
+
     #[clap(alias = "c")]
     Chrono {},
 
@@ -46,6 +79,47 @@ enum Commands {
         #[arg(required = true)]
         duration: Vec<String>,
     },
+
+    // Display the time of several zones, set in the [world] section
+    #[clap(alias = "w")]
+    World {},
+
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    Themes {
+        #[command(subcommand)]
+        command: ThemeCommands,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ThemeCommands {
+    // Print the names of the available themes
+    List,
+
+    // Display the digit, accent and background colors of a theme, or of all of them
+    Preview {
+        #[arg(value_name = "NAME")]
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    // Validate a config file, the current one if none is given
+    Check {
+        #[arg(value_name = "FILE")]
+        file: Option<String>,
+    },
+
+    // Show the keys of a config file which differ from the defaults
+    Diff {
+        #[arg(value_name = "FILE")]
+        file: Option<String>,
+    },
 }
 
 fn main() -> io::Result<()> {
@@ -101,12 +175,109 @@ fn main() -> io::Result<()> {
         panic!("ERROR: Configuration file not found");
     }
 
-    // Enable debug mode if needed, and load config
-    let debug_mode = match &cli.command {
-        Some(Commands::Debug {}) => true,
-        _ => false,
+    // Handle config commands and exit
+    if let Some(Commands::Config { command }) = &cli.command {
+        match command {
+            ConfigCommands::Check { file } => {
+                let config_file = file.as_ref().map(PathBuf::from).unwrap_or(config_file);
+                if let Err(errors) = config::check_file(config_file.clone()) {
+                    report_config_errors(&config_file, &errors);
+                }
+
+                println!("{}: OK", config_file.display());
+            }
+            ConfigCommands::Diff { file } => {
+                let config_file = file.as_ref().map(PathBuf::from).unwrap_or(config_file);
+                let diffs = config::diff_file(config_file.clone())
+                    .unwrap_or_else(|errors| report_config_errors(&config_file, &errors));
+
+                let unset = || "(unset)".to_owned();
+                for diff in diffs {
+                    println!(
+                        "{}.{}: {} -> {}",
+                        diff.section,
+                        diff.key,
+                        diff.default.unwrap_or_else(unset),
+                        diff.value.unwrap_or_else(unset)
+                    );
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Handle theme commands and exit
+    if let Some(Commands::Themes { command }) = &cli.command {
+        match command {
+            ThemeCommands::List => {
+                for name in config::theme_names() {
+                    println!("{}", name);
+                }
+            }
+            ThemeCommands::Preview { name } => {
+                let names = match name {
+                    Some(name) => vec![name.clone()],
+                    None => config::theme_names(),
+                };
+
+                // Themes are loaded on top of the current config, like in debug mode
+                for name in names {
+                    let options = LoadOptions {
+                        mode: "debug",
+                        overrides: vec![Override {
+                            section: "styling".to_owned(),
+                            key: "theme".to_owned(),
+                            value: name.clone(),
+                            origin: "themes preview".to_owned(),
+                        }],
+                        ..LoadOptions::default()
+                    };
+                    let mut config = config::load_from_file(config_file.clone(), &options)
+                        .unwrap_or_else(|errors| report_config_errors(&config_file, &errors));
+                    debug::print_theme(&name, &mut config)?;
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    // --tz and --utc are shortcuts for the timezone key
+    let timezone = cli
+        .tz
+        .map(|zone| (zone, "--tz"))
+        .or(cli.utc.then(|| ("UTC".to_owned(), "--utc")))
+        .map(|(zone, flag)| Override {
+            section: "general".to_owned(),
+            key: "timezone".to_owned(),
+            value: zone,
+            origin: flag.to_owned(),
+        })
+        .into_iter()
+        .collect();
+
+    // Enable debug mode if needed, and load config for the mode
+    let debug_mode = matches!(&cli.command, Some(Commands::Debug {}));
+    let options = LoadOptions {
+        mode: match &cli.command {
+            Some(Commands::Debug {}) => "debug",
+            Some(Commands::Chrono {}) => "chrono",
+            Some(Commands::Timer { .. }) => "timer",
+            Some(Commands::World {}) => "world",
+            _ => "clock",
+        },
+        profile: cli.profile,
+        overrides: [config::env_overrides(), cli.overrides, timezone].concat(),
+        alarms: match &cli.command {
+            Some(Commands::Clock { alarm }) => alarm.clone(),
+            _ => Vec::new(),
+        },
+    };
+    let mut config = match config::load_from_file(config_file.clone(), &options) {
+        Ok(config) => config,
+        Err(errors) => report_config_errors(&config_file, &errors),
     };
-    let mut config = config::load_from_file(config_file, debug_mode);
     let mut stdout = io::stdout();
 
     // Print debug infos
@@ -115,24 +286,35 @@ fn main() -> io::Result<()> {
         return Ok(());
     }
 
+    // Create the appropriate mode
+    let mut mode: Box<dyn Mode> = match &cli.command {
+        Some(Commands::Chrono {}) => Box::new(modes::chrono::Chrono::new()),
+        Some(Commands::Timer { duration }) => {
+            let duration = parse_duration::parse(&duration.join(" "))
+                .unwrap_or_else(|_| eprintln_quit!("Invalid duration provided"));
+            Box::new(modes::timer::TimerMode::new(duration))
+        }
+        Some(Commands::World {}) => Box::new(modes::world::World::new()),
+        Some(Commands::Debug {} | Commands::Config { .. } | Commands::Themes { .. }) => {
+            unreachable!()
+        }
+        Some(Commands::Clock { .. }) | None => Box::new(modes::clock::Clock::new()),
+    };
+
     // Switch to alternate screen, hide the cursor and enable raw mode
     execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
-    let _ = terminal::enable_raw_mode()?;
+    terminal::enable_raw_mode()?;
 
-    // Start the appropriate mode
-    match &cli.command {
-        Some(Commands::Chrono {}) => modes::chrono::main_loop(&mut config)?,
-        Some(Commands::Timer { duration }) => {
-            let duration = duration.join(" ");
-            modes::timer::main_loop(&mut config, &duration)?
-        }
-        Some(Commands::Debug {}) => unreachable!(),
-        None => modes::clock::main_loop(&mut config)?,
-    }
+    let result = run(
+        mode.as_mut(),
+        &mut config,
+        ConfigWatcher::new(config_file, options),
+    );
 
     // Disale raw mode, leave the alternate screen and show the cursor back
-    let _ = terminal::disable_raw_mode()?;
+    terminal::disable_raw_mode()?;
     execute!(stdout, terminal::LeaveAlternateScreen, cursor::Show)?;
+    result?;
 
     // Be polite
     if config.be_polite {
@@ -141,3 +323,103 @@ fn main() -> io::Result<()> {
 
     Ok(())
 }
+
+// Report every error at once, so they can all be fixed in one go
+fn report_config_errors(config_file: &Path, errors: &[ConfigError]) -> ! {
+    for error in errors {
+        eprintln!("ERROR: {}", describe_config_error(config_file, error));
+    }
+    process::exit(1)
+}
+
+// Errors of the file are prefixed with its path, the ones of overrides name their flag or
+// environment variable instead
+fn describe_config_error(config_file: &Path, error: &ConfigError) -> String {
+    if error.in_file() {
+        format!("{}: {}", config_file.display(), error)
+    } else {
+        error.to_string()
+    }
+}
+
+// How long config reload errors stay on screen
+const BANNER_DURATION: Duration = Duration::from_secs(5);
+
+fn run(mode: &mut dyn Mode, config: &mut Config, mut watcher: ConfigWatcher) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    let mut renderer = Renderer::new(CrosstermBackend::new(io::stdout(), config.color_support))?;
+    let mut title = String::new();
+    let mut banner: Option<(String, Instant)> = None;
+    let banner_style = TextStyle::from(Color::Red, Attribute::Bold.into());
+
+    loop {
+        // Handle events
+        while event::poll(Duration::ZERO)? {
+            let event = event::read()?;
+            match event {
+                // Handle CTRL-C
+                Event::Key(e)
+                    if e.code == KeyCode::Char('c')
+                        && e.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    return Ok(());
+                }
+                // Handle resize, the next frame will be fully redrawn
+                Event::Resize(width, height) => {
+                    renderer.resize(width, height);
+                    mode.handle_event(&event);
+                }
+                _ => mode.handle_event(&event),
+            }
+        }
+
+        // Reload config when it changes, mode state is kept
+        match watcher.poll() {
+            Some(Ok(new_config)) => {
+                *config = new_config;
+                banner = None;
+
+                // Color support may have changed, start again from a blank screen
+                renderer =
+                    Renderer::new(CrosstermBackend::new(io::stdout(), config.color_support))?;
+            }
+            Some(Err(errors)) => {
+                let mut message = describe_config_error(watcher.path(), &errors[0]);
+                if errors.len() > 1 {
+                    message += &format!(" (+{} more)", errors.len() - 1);
+                }
+                banner = Some((message, Instant::now()));
+            }
+            None => {}
+        }
+
+        mode.update(config);
+        for effect in mode.take_effects() {
+            match effect {
+                Effect::Bell => renderer.bell()?,
+                Effect::Command(command, args) => utils::spawn_command(&command, &args),
+            }
+        }
+
+        // Update title only when it changes
+        if mode.title() != title {
+            title = mode.title();
+            queue!(stdout, terminal::SetTitle(&title))?;
+        }
+
+        // Render
+        mode.draw(renderer.buffer_mut(), config);
+        if let Some((message, shown_at)) = &banner {
+            if shown_at.elapsed() < BANNER_DURATION {
+                rendering::draw_text_centered(renderer.buffer_mut(), message, 0, &banner_style);
+            } else {
+                banner = None;
+            }
+        }
+        renderer.flush()?;
+
+        config.update_colors();
+
+        thread::sleep(Duration::from_millis(1000 / config.fps));
+    }
+}
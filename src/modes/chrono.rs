@@ -1,22 +1,18 @@
 use std::{
     cmp::min,
-    io::{self, Write},
-    thread,
     time::{Duration, Instant},
 };
 
-use crossterm::{
-    event::{self, Event, KeyCode, KeyModifiers},
-    queue,
-    terminal::{self, ClearType},
-};
+use crossterm::event::{Event, KeyCode};
 
 use crate::utils;
 use crate::{
     config::Config,
-    rendering::{self, symbols},
+    rendering::{self, buffer::Buffer, layout::Rect},
 };
 
+use super::Mode;
+
 struct Lapse {
     pub time: Duration,
     pub delta: Duration,
@@ -66,141 +62,178 @@ impl Chronometer {
     }
 }
 
-pub fn main_loop(config: &mut Config) -> io::Result<()> {
-    let mut stdout = io::stdout();
-
-    let mut chronometer = Chronometer::new();
-    chronometer.start();
-
-    let mut lapses: Vec<Lapse> = vec![];
-    let mut scroll_offset: usize = 0;
-
-    let mut quit = false;
-    while !quit {
-        // Handle events
-        while event::poll(Duration::ZERO)? {
-            match event::read()? {
-                Event::Key(e) => match e.code {
-                    // Handle CTRL-C
-                    KeyCode::Char('c') => {
-                        if e.modifiers.contains(KeyModifiers::CONTROL) {
-                            quit = true;
-                        }
-                    }
-                    // Handle pause
-                    KeyCode::Char(' ') => {
-                        chronometer.toggle_pause();
-                    }
-                    // Handle reset
-                    KeyCode::Char('r') => {
-                        chronometer.reset();
-                        lapses.clear();
-                        scroll_offset = 0;
-                    }
-                    // Handle lapses
-                    KeyCode::Char('l') => {
-                        let time = chronometer.elapsed();
-                        let delta = if let Some(last_lap) = lapses.last() {
-                            Duration::from_secs(time.as_secs() - last_lap.time.as_secs())
-                        } else {
-                            time
-                        };
-
-                        lapses.push(Lapse { time, delta });
-
-                        scroll_offset = 0;
-                    }
-                    // Handle scroll in lapses list
-                    KeyCode::Down => {
-                        scroll_offset = min(scroll_offset + 1, lapses.len());
-                    }
-                    KeyCode::Up => {
-                        scroll_offset = if scroll_offset == 0 {
-                            0
-                        } else {
-                            scroll_offset - 1
-                        };
-                    }
-                    KeyCode::PageDown => {
-                        scroll_offset = lapses.len();
-                    }
-                    KeyCode::PageUp => {
-                        scroll_offset = 0;
-                    }
-                    _ => {}
-                },
+// Status, empty row and a few lapses, the list grows into the padding if there is room
+const LAPSES_LINES: i16 = 7;
+
+pub struct Chrono {
+    chronometer: Chronometer,
+    lapses: Vec<Lapse>,
+    scroll_offset: usize,
+}
+
+impl Chrono {
+    pub fn new() -> Self {
+        let mut chronometer = Chronometer::new();
+        chronometer.start();
+
+        Chrono {
+            chronometer,
+            lapses: vec![],
+            scroll_offset: 0,
+        }
+    }
+}
+
+impl Mode for Chrono {
+    fn title(&self) -> String {
+        "tlock - chrono".to_owned()
+    }
+
+    fn status(&self) -> Option<&str> {
+        if self.chronometer.is_paused() {
+            Some("[PAUSE]")
+        } else {
+            None
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Key(e) = event {
+            match e.code {
+                // Handle pause
+                KeyCode::Char(' ') => {
+                    self.chronometer.toggle_pause();
+                }
+                // Handle reset
+                KeyCode::Char('r') => {
+                    self.chronometer.reset();
+                    self.lapses.clear();
+                    self.scroll_offset = 0;
+                }
+                // Handle lapses
+                KeyCode::Char('l') => {
+                    let time = self.chronometer.elapsed();
+                    let delta = if let Some(last_lap) = self.lapses.last() {
+                        Duration::from_secs(time.as_secs() - last_lap.time.as_secs())
+                    } else {
+                        time
+                    };
+
+                    self.lapses.push(Lapse { time, delta });
+
+                    self.scroll_offset = 0;
+                }
+                // Handle scroll in lapses list
+                KeyCode::Down => {
+                    self.scroll_offset = min(self.scroll_offset + 1, self.lapses.len());
+                }
+                KeyCode::Up => {
+                    self.scroll_offset = self.scroll_offset.saturating_sub(1);
+                }
+                KeyCode::PageDown => {
+                    self.scroll_offset = self.lapses.len();
+                }
+                KeyCode::PageUp => {
+                    self.scroll_offset = 0;
+                }
                 _ => {}
             }
         }
+    }
 
-        // Clear frame
-        queue!(stdout, terminal::Clear(ClearType::All))?;
+    fn render(&mut self, buffer: &mut Buffer, config: &Config) -> Rect {
+        // Display time
+        let elapsed = utils::format_duration(self.chronometer.elapsed());
+        let time_area = rendering::draw_time(
+            buffer,
+            &config.digits,
+            &elapsed,
+            LAPSES_LINES,
+            &config.elements.digits,
+            &config.elements.colon,
+        );
 
-        // Render
-        render_frame(&config, &chronometer, &lapses, &mut scroll_offset)?;
+        // Display lapses, below the status and an empty row
+        let (_, height) = buffer.size();
+        let y = time_area.bottom() + 2;
+        let max_items = (height - y - 1).clamp(0, 10) as usize;
 
-        config.color.update();
+        if self.lapses.len() <= max_items {
+            self.scroll_offset = 0;
+        } else if self.scroll_offset > self.lapses.len() - max_items {
+            self.scroll_offset = self.lapses.len() - max_items;
+        }
 
-        stdout.flush()?;
+        // Iterate over lapses, skipping with scroll offset and taxing N items
+        for (i, lapse) in self
+            .lapses
+            .iter()
+            .rev()
+            .skip(self.scroll_offset)
+            .take(max_items)
+            .enumerate()
+        {
+            let delta = utils::format_duration(lapse.delta);
+            let time = utils::format_duration(lapse.time);
+
+            let lapse = format!(
+                "#{:02}  --  +{}  --  {}",
+                self.lapses.len() - i - self.scroll_offset,
+                delta,
+                time
+            );
+            rendering::draw_text_centered(buffer, &lapse, y + i as i16, &config.elements.laps);
+        }
 
-        thread::sleep(Duration::from_millis(1000 / config.fps));
+        time_area
     }
-
-    Ok(())
 }
 
-fn render_frame(
-    config: &Config,
-    chronometer: &Chronometer,
-    lapses: &Vec<Lapse>,
-    scroll_offset: &mut usize,
-) -> io::Result<()> {
-    let color = config.color.get_value();
-
-    // Display time
-    let elapsed = utils::format_duration(chronometer.elapsed());
-    rendering::draw_time(&elapsed, color)?;
-
-    // Display lapses
-    let (width, height) = rendering::get_terminal_size()?;
-    let y = height / 2 + symbols::SYMBOL_HEIGHT as i16 / 2 + 2;
-    let max_items = min(10, height - y - 1) as usize;
-
-    if lapses.len() <= max_items {
-        *scroll_offset = 0;
-    } else if *scroll_offset > lapses.len() - max_items {
-        *scroll_offset = lapses.len() - max_items;
-    }
-
-    // Iterate over lapses, skipping with scroll offset and taxing N items
-    for (i, lapse) in lapses
-        .iter()
-        .rev()
-        .skip(*scroll_offset)
-        .take(max_items)
-        .enumerate()
-    {
-        let delta = utils::format_duration(lapse.delta);
-        let time = utils::format_duration(lapse.time);
-
-        let lapse = format!(
-            "#{:02}  --  +{}  --  {}",
-            lapses.len() - i - *scroll_offset,
-            delta,
-            time
-        );
-        let x = width / 2 - (lapse.len() as i16) / 2;
-        rendering::draw_text(&lapse, x, y + i as i16, color)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config, rendering::snapshot::assert_snapshot};
+
+    fn paused_at(seconds: u64) -> Chrono {
+        let mut chrono = Chrono::new();
+        chrono.chronometer.reset();
+        chrono.chronometer.paused_duration = Duration::from_secs(seconds);
+        chrono
+    }
+
+    fn render(chrono: &mut Chrono, width: u16, height: u16) -> Buffer {
+        let config = config::load_default();
+
+        let mut buffer = Buffer::new(width, height);
+        chrono.draw(&mut buffer, &config);
+        buffer
+    }
+
+    #[test]
+    fn chrono_paused_80x24() {
+        let mut chrono = paused_at(83);
+        assert_snapshot("chrono_paused_80x24", &render(&mut chrono, 80, 24));
     }
 
-    // Display pause state
-    if chronometer.is_paused() {
-        let text = "[PAUSE]";
-        let x = width / 2 - (text.len() as i16) / 2 - 1;
-        let y = y - symbols::SYMBOL_HEIGHT as i16 + symbols::SYMBOL_HEIGHT as i16 / 2 + 1;
+    #[test]
+    fn chrono_laps_80x24() {
+        let mut chrono = paused_at(0);
+        for seconds in [12, 30, 95] {
+            chrono.chronometer.paused_duration = Duration::from_secs(seconds);
+            chrono.handle_event(&Event::Key(KeyCode::Char('l').into()));
+        }
 
-        rendering::draw_text(text, x, y, color)?;
+        assert_snapshot("chrono_laps_80x24", &render(&mut chrono, 80, 24));
     }
 
-    Ok(())
+    #[test]
+    fn chrono_laps_clipped_60x16() {
+        let mut chrono = paused_at(0);
+        for seconds in 1..=20 {
+            chrono.chronometer.paused_duration = Duration::from_secs(seconds * 10);
+            chrono.handle_event(&Event::Key(KeyCode::Char('l').into()));
+        }
+
+        assert_snapshot("chrono_laps_clipped_60x16", &render(&mut chrono, 60, 16));
+    }
 }
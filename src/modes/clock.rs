@@ -1,77 +1,608 @@
-use std::{
-    io::{self, Write},
-    thread,
-    time::Duration,
-};
+use std::{collections::VecDeque, mem};
 
-use chrono;
-use crossterm::{
-    event::{self, Event, KeyCode, KeyModifiers},
-    queue,
-    terminal::{self, ClearType},
-};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use crossterm::event::{Event, KeyCode};
 
 use crate::{
+    alarm::{Alarm, Chimes},
     config::Config,
-    rendering::{self, symbols},
+    rendering::{
+        self,
+        buffer::Buffer,
+        calendar::{self, CalendarStyles, Placement},
+        layout::Rect,
+    },
+    utils,
 };
 
-pub fn main_loop(config: &mut Config) -> io::Result<()> {
-    let mut stdout = io::stdout();
-
-    let mut quit = false;
-    while !quit {
-        // Handle events
-        while event::poll(Duration::ZERO)? {
-            match event::read()? {
-                Event::Key(e) => match e.code {
-                    // Handle CTRL-C
-                    KeyCode::Char('c') => {
-                        if e.modifiers.contains(KeyModifiers::CONTROL) {
-                            quit = true;
-                        }
+use super::{Effect, Mode};
+
+// Empty row for the status, and the date
+const DATE_LINES: i16 = 2;
+
+// How long the digits flash when the clock chimes, and how fast
+const CHIME_FLASH: i64 = 1000;
+const CHIME_BLINK: i64 = 250;
+
+// Alarm going off, until it's snoozed or dismissed
+struct Ringing {
+    alarm: Alarm,
+    since: DateTime<Utc>,
+    last_bell: Option<DateTime<Utc>>,
+    status: String,
+}
+
+struct Snoozed {
+    alarm: Alarm,
+    until: NaiveDateTime,
+    status: String,
+}
+
+pub struct Clock {
+    date_time: DateTime<Utc>,
+    // Wall clock time of the last update, alarms set since then go off
+    last_check: Option<NaiveDateTime>,
+    ringing: Option<Ringing>,
+    // Alarms due while another one rings, they ring once it's snoozed or dismissed
+    waiting: VecDeque<Alarm>,
+    // Earliest first
+    snoozed: Vec<Snoozed>,
+    chimed_at: Option<DateTime<Utc>>,
+    effects: Vec<Effect>,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Clock::at(Utc::now())
+    }
+
+    fn at(date_time: DateTime<Utc>) -> Self {
+        Clock {
+            date_time,
+            last_check: None,
+            ringing: None,
+            waiting: VecDeque::new(),
+            snoozed: Vec::new(),
+            chimed_at: None,
+            effects: Vec::new(),
+        }
+    }
+
+    // Ring the alarms and chimes due between the last check and now, each one exactly once
+    fn check_events(&mut self, config: &Config) {
+        let now = utils::local_date_time(self.date_time, config.timezone);
+        if let Some(last_check) = self.last_check {
+            // Snoozed alarms come first, then the ones set for this time
+            let (due, snoozed): (Vec<Snoozed>, _) = mem::take(&mut self.snoozed)
+                .into_iter()
+                .partition(|snoozed| last_check < snoozed.until && snoozed.until <= now);
+            self.snoozed = snoozed;
+            self.waiting
+                .extend(due.into_iter().map(|snoozed| snoozed.alarm));
+            self.waiting.extend(
+                config
+                    .alarms
+                    .iter()
+                    .filter(|alarm| alarm.goes_off_between(last_check, now))
+                    .cloned(),
+            );
+
+            if config.chimes.interval.chimes_between(last_check, now) {
+                self.chime(&config.chimes);
+            }
+        }
+        self.last_check = Some(now);
+
+        // Alarms ring one after another
+        if self.ringing.is_none() {
+            if let Some(alarm) = self.waiting.pop_front() {
+                self.ring(alarm);
+            }
+        }
+
+        // The bell rings every second, as long as the alarm does
+        if let Some(ringing) = &mut self.ringing {
+            let rang_recently = ringing
+                .last_bell
+                .is_some_and(|at| self.date_time - at < Duration::seconds(1));
+            if ringing.alarm.bell && !rang_recently {
+                ringing.last_bell = Some(self.date_time);
+                self.effects.push(Effect::Bell);
+            }
+        }
+    }
+
+    fn chime(&mut self, chimes: &Chimes) {
+        if chimes.bell {
+            self.effects.push(Effect::Bell);
+        }
+        if let Some(command) = &chimes.command {
+            self.effects
+                .push(Effect::Command(command.clone(), Vec::new()));
+        }
+        if let Some(sound) = &chimes.sound {
+            self.effects
+                .push(Effect::Command(chimes.player.clone(), vec![sound.clone()]));
+        }
+        if chimes.flash {
+            self.chimed_at = Some(self.date_time);
+        }
+    }
+
+    // Whether the digits are in their chime style, they blink for a moment after a chime
+    fn chime_flash(&self) -> bool {
+        self.chimed_at.is_some_and(|at| {
+            let elapsed = (self.date_time - at).num_milliseconds();
+            elapsed < CHIME_FLASH && (elapsed / CHIME_BLINK) % 2 == 0
+        })
+    }
+
+    // Draw the time, with the date under it and `lines_below` rows free under the date
+    fn draw_time_and_date(
+        &self,
+        buffer: &mut Buffer,
+        config: &Config,
+        (time, date): (&str, &str),
+        lines_below: i16,
+    ) -> Rect {
+        let (style, colon_style) = if self.chime_flash() {
+            (&config.elements.chime, &config.elements.chime)
+        } else {
+            (&config.elements.digits, &config.elements.colon)
+        };
+        let time_area = rendering::draw_time(
+            buffer,
+            &config.digits,
+            time,
+            DATE_LINES + lines_below,
+            style,
+            colon_style,
+        );
+        rendering::draw_text_centered(buffer, date, time_area.bottom() + 1, &config.elements.date);
+
+        time_area
+    }
+
+    fn ring(&mut self, alarm: Alarm) {
+        if let Some(command) = &alarm.command {
+            self.effects
+                .push(Effect::Command(command.clone(), Vec::new()));
+        }
+
+        self.ringing = Some(Ringing {
+            status: format!("[ALARM] {} - s: snooze, d: dismiss", alarm.label),
+            alarm,
+            since: self.date_time,
+            last_bell: None,
+        });
+    }
+}
+
+impl Mode for Clock {
+    fn title(&self) -> String {
+        match &self.ringing {
+            Some(ringing) => format!("tlock - {}", ringing.alarm.label),
+            None => "tlock".to_owned(),
+        }
+    }
+
+    fn status(&self) -> Option<&str> {
+        match (&self.ringing, self.snoozed.first()) {
+            (Some(ringing), _) => Some(&ringing.status),
+            (None, Some(snoozed)) => Some(&snoozed.status),
+            (None, None) => None,
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Key(e) = event {
+            match e.code {
+                // Handle snooze, the alarm rings again after a while
+                KeyCode::Char('s') => {
+                    if let (Some(ringing), Some(now)) = (self.ringing.take(), self.last_check) {
+                        let until = now + ringing.alarm.snooze;
+                        let i = self
+                            .snoozed
+                            .partition_point(|snoozed| snoozed.until <= until);
+                        self.snoozed.insert(
+                            i,
+                            Snoozed {
+                                status: format!(
+                                    "[SNOOZE] {} until {}",
+                                    ringing.alarm.label,
+                                    until.format("%H:%M")
+                                ),
+                                alarm: ringing.alarm,
+                                until,
+                            },
+                        );
+                    }
+                }
+                // Handle dismiss, of the ringing alarm, or of the snoozed ones
+                KeyCode::Char('d') => {
+                    if self.ringing.is_some() {
+                        self.ringing = None;
+                    } else {
+                        self.snoozed.clear();
                     }
-                    _ => {}
-                },
+                }
                 _ => {}
             }
         }
+    }
 
-        // Clear frame
-        queue!(stdout, terminal::Clear(ClearType::All))?;
+    fn update(&mut self, config: &Config) {
+        self.date_time = Utc::now();
+        self.check_events(config);
+    }
 
-        // Render
-        render_frame(&config)?;
+    fn take_effects(&mut self) -> Vec<Effect> {
+        mem::take(&mut self.effects)
+    }
 
-        config.color.update();
+    // Flash twice per second while an alarm rings
+    fn flash(&self) -> bool {
+        self.ringing.as_ref().is_some_and(|ringing| {
+            let elapsed = (self.date_time - ringing.since).num_milliseconds();
+            ringing.alarm.flash && (elapsed / 500) % 2 == 0
+        })
+    }
 
-        stdout.flush()?;
+    fn render(&mut self, buffer: &mut Buffer, config: &Config) -> Rect {
+        let date_time = self.date_time;
+        let time = utils::format_date_time(date_time, config.timezone, &config.time_format);
+        let date = utils::format_date_time(date_time, config.timezone, &config.date_format);
+        let texts = (time.as_str(), date.as_str());
 
-        thread::sleep(Duration::from_millis(1000 / config.fps));
-    }
+        let calendar = &config.calendar;
+        let (width, _) = buffer.size();
+        let (calendar_width, calendar_height) = calendar.size();
+        let today = utils::local_date_time(date_time, config.timezone).date();
+        let styles = CalendarStyles {
+            days: &config.elements.date,
+            header: &config.elements.header,
+            today: &config.elements.highlight,
+        };
 
-    Ok(())
+        match calendar.placement_in(buffer.size(), &config.digits, &time, DATE_LINES) {
+            Placement::None => self.draw_time_and_date(buffer, config, texts, 0),
+            Placement::Below => {
+                let lines_below = calendar::GAP_BELOW + calendar_height;
+                let time_area = self.draw_time_and_date(buffer, config, texts, lines_below);
+
+                let x = (width - calendar_width) / 2;
+                let y = time_area.bottom() + DATE_LINES + calendar::GAP_BELOW;
+                calendar::draw_calendar(buffer, calendar, today, (x, y), &styles);
+
+                time_area
+            }
+            Placement::Beside => {
+                // The time and the date are drawn on their own, then centered along with
+                // the calendar
+                let (_, height) = buffer.size();
+                let side_width = width - calendar_width - calendar::GAP_BESIDE;
+                let mut side = Buffer::new(side_width.max(0) as u16, height as u16);
+                let time_area = self.draw_time_and_date(&mut side, config, texts, 0);
+
+                let text_width = time_area.width.max(date.chars().count() as i16);
+                let text_x = (side_width - text_width) / 2;
+                let x = (width - text_width - calendar::GAP_BESIDE - calendar_width) / 2;
+                buffer.blit(&side, x - text_x, 0);
+
+                let calendar_x = x + text_width + calendar::GAP_BESIDE;
+                let calendar_y =
+                    time_area.y + (time_area.height + DATE_LINES - calendar_height) / 2;
+                calendar::draw_calendar(buffer, calendar, today, (calendar_x, calendar_y), &styles);
+
+                Rect {
+                    x: time_area.x + x - text_x,
+                    ..time_area
+                }
+            }
+        }
+    }
 }
 
-fn render_frame(config: &Config) -> io::Result<()> {
-    let color = config.color.get_value();
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Weekday};
+    use chrono_tz::Tz;
+    use crossterm::{
+        event::{KeyEvent, KeyModifiers},
+        style::Color,
+    };
 
-    let date_time = chrono::Local::now();
+    use super::*;
+    use crate::{
+        alarm::{self, ChimeInterval},
+        config,
+        rendering::{
+            calendar::Calendar,
+            canvas::Style,
+            frame::{Border, Frame},
+            snapshot::assert_snapshot,
+        },
+    };
 
-    // Display time
-    let time = date_time.time().format(&config.time_format).to_string();
-    rendering::draw_time(&time, color)?;
+    fn render(width: u16, height: u16) -> Buffer {
+        render_with_style(width, height, Style::Block)
+    }
 
-    // Display date
-    let date = date_time
-        .date_naive()
-        .format(&config.date_format.to_owned())
-        .to_string();
+    fn render_with_style(width: u16, height: u16, style: Style) -> Buffer {
+        let mut config = config::load_default();
+        config.timezone = Some(Tz::UTC);
+        config.date_format = "%d/%m/%Y".to_owned();
+        config.digits.style = style;
+        render_with_config(width, height, &config)
+    }
+
+    fn render_with_config(width: u16, height: u16, config: &Config) -> Buffer {
+        let mut clock = Clock::at(Utc.with_ymd_and_hms(2024, 1, 27, 13, 37, 0).unwrap());
+
+        let mut buffer = Buffer::new(width, height);
+        clock.draw(&mut buffer, config);
+        buffer
+    }
+
+    #[test]
+    fn clock_80x24() {
+        assert_snapshot("clock_80x24", &render(80, 24));
+    }
+
+    #[test]
+    fn clock_160x48() {
+        assert_snapshot("clock_160x48", &render(160, 48));
+    }
 
-    let (width, height) = rendering::get_terminal_size()?;
-    let x = width / 2 - (date.len() as i16) / 2;
-    let y = height / 2 + symbols::SYMBOL_HEIGHT as i16 / 2 + 2;
-    rendering::draw_text(&date, x, y - 1, color)?;
+    #[test]
+    fn clock_41x11() {
+        assert_snapshot("clock_41x11", &render(41, 11));
+    }
+
+    #[test]
+    fn clock_20x6() {
+        assert_snapshot("clock_20x6", &render(20, 6));
+    }
+
+    #[test]
+    fn clock_half_block_40x12() {
+        assert_snapshot(
+            "clock_half_block_40x12",
+            &render_with_style(40, 12, Style::HalfBlock),
+        );
+    }
+
+    #[test]
+    fn clock_quadrant_40x12() {
+        assert_snapshot(
+            "clock_quadrant_40x12",
+            &render_with_style(40, 12, Style::Quadrant),
+        );
+    }
+
+    #[test]
+    fn clock_braille_40x12() {
+        assert_snapshot(
+            "clock_braille_40x12",
+            &render_with_style(40, 12, Style::Braille),
+        );
+    }
+
+    #[test]
+    fn clock_letters_80x16() {
+        let mut config = config::load_default();
+        config.timezone = Some(Tz::UTC);
+        config.time_format = "%a %H.%M".to_owned();
+        config.date_format = "%d/%m/%Y".to_owned();
+
+        assert_snapshot("clock_letters_80x16", &render_with_config(80, 16, &config));
+    }
+
+    #[test]
+    fn clock_zone_60x12() {
+        let mut config = config::load_default();
+        config.timezone = Some(Tz::Europe__Paris);
+        config.date_format = "%d/%m/%Y %Z (%z)".to_owned();
+        assert_snapshot("clock_zone_60x12", &render_with_config(60, 12, &config));
+    }
 
-    Ok(())
+    #[test]
+    fn clock_framed_60x16() {
+        let mut config = config::load_default();
+        config.timezone = Some(Tz::UTC);
+        config.date_format = "%d/%m/%Y".to_owned();
+        config.frame = Frame {
+            border: Border::Rounded,
+            padding: 1,
+            fill: None,
+        };
+        config.digits.scaling.frame = config.frame.thickness() as u16;
+        config.digits.style = Style::HalfBlock;
+        config.digits.shadow = Some(Color::DarkGrey);
+        assert_snapshot("clock_framed_60x16", &render_with_config(60, 16, &config));
+    }
+
+    fn render_with_calendar(width: u16, height: u16, placement: Placement) -> Buffer {
+        let mut config = config::load_default();
+        config.timezone = Some(Tz::UTC);
+        config.date_format = "%d/%m/%Y".to_owned();
+        config.calendar = Calendar {
+            placement,
+            first_weekday: Weekday::Mon,
+            week_numbers: true,
+            previous: false,
+            next: true,
+        };
+        render_with_config(width, height, &config)
+    }
+
+    #[test]
+    fn clock_calendar_below_80x24() {
+        assert_snapshot(
+            "clock_calendar_below_80x24",
+            &render_with_calendar(80, 24, Placement::Below),
+        );
+    }
+
+    #[test]
+    fn clock_calendar_beside_100x14() {
+        assert_snapshot(
+            "clock_calendar_beside_100x14",
+            &render_with_calendar(100, 14, Placement::Beside),
+        );
+    }
+
+    #[test]
+    fn calendar_is_hidden_when_too_small() {
+        let hidden = render_with_calendar(41, 11, Placement::Below);
+        assert_eq!(hidden, render(41, 11));
+        let hidden = render_with_calendar(41, 11, Placement::Beside);
+        assert_eq!(hidden, render(41, 11));
+    }
+
+    #[test]
+    fn alarms_ring_once_until_snoozed_or_dismissed() {
+        let mut config = config::load_default();
+        config.timezone = Some(Tz::UTC);
+        config.alarms = vec![Alarm {
+            time: alarm::parse_time("07:30").unwrap(),
+            days: [true; 7],
+            label: "Wake up".to_owned(),
+            bell: true,
+            flash: true,
+            command: Some("true".to_owned()),
+            snooze: Duration::minutes(5),
+        }];
+
+        let at = |hour, minute, second| {
+            Utc.with_ymd_and_hms(2024, 1, 27, hour, minute, second)
+                .unwrap()
+        };
+        let mut clock = Clock::at(at(7, 29, 59));
+        let step = |clock: &mut Clock, date_time: DateTime<Utc>| {
+            clock.date_time = date_time;
+            clock.check_events(&config);
+            clock.take_effects()
+        };
+        let press = |clock: &mut Clock, c| {
+            clock.handle_event(&Event::Key(KeyEvent::new(
+                KeyCode::Char(c),
+                KeyModifiers::NONE,
+            )));
+        };
+
+        assert!(step(&mut clock, at(7, 29, 59)).is_empty());
+        assert_eq!(
+            step(&mut clock, at(7, 30, 0)),
+            vec![Effect::Command("true".to_owned(), Vec::new()), Effect::Bell]
+        );
+        assert!(clock.flash());
+        assert_eq!(clock.title(), "tlock - Wake up");
+
+        // The bell keeps ringing every second, the command only runs once
+        assert!(step(&mut clock, at(7, 30, 0)).is_empty());
+        assert_eq!(step(&mut clock, at(7, 30, 1)), vec![Effect::Bell]);
+
+        press(&mut clock, 's');
+        assert_eq!(clock.status(), Some("[SNOOZE] Wake up until 07:35"));
+        assert!(!clock.flash());
+        assert!(step(&mut clock, at(7, 34, 0)).is_empty());
+        // Even if frames are skipped
+        assert_eq!(step(&mut clock, at(7, 36, 0)).len(), 2);
+
+        press(&mut clock, 'd');
+        assert_eq!(clock.status(), None);
+        assert!(step(&mut clock, at(7, 45, 0)).is_empty());
+    }
+
+    #[test]
+    fn alarms_at_the_same_time_ring_one_after_another() {
+        let mut config = config::load_default();
+        config.timezone = Some(Tz::UTC);
+        let alarm = |label: &str, command: &str| Alarm {
+            time: alarm::parse_time("07:30").unwrap(),
+            days: [true; 7],
+            label: label.to_owned(),
+            bell: false,
+            flash: true,
+            command: Some(command.to_owned()),
+            snooze: Duration::minutes(5),
+        };
+        config.alarms = vec![alarm("Wake up", "wake"), alarm("Meds", "meds")];
+
+        let mut clock = Clock::at(Utc.with_ymd_and_hms(2024, 1, 27, 7, 29, 59).unwrap());
+        let step = |clock: &mut Clock| {
+            clock.check_events(&config);
+            clock.date_time += Duration::seconds(1);
+            clock.take_effects()
+        };
+        let press = |clock: &mut Clock, c| {
+            clock.handle_event(&Event::Key(KeyEvent::new(
+                KeyCode::Char(c),
+                KeyModifiers::NONE,
+            )));
+        };
+
+        assert!(step(&mut clock).is_empty());
+        assert_eq!(
+            step(&mut clock),
+            vec![Effect::Command("wake".to_owned(), Vec::new())]
+        );
+        assert_eq!(clock.title(), "tlock - Wake up");
+        assert!(step(&mut clock).is_empty());
+
+        press(&mut clock, 's');
+        assert_eq!(
+            step(&mut clock),
+            vec![Effect::Command("meds".to_owned(), Vec::new())]
+        );
+        assert_eq!(clock.title(), "tlock - Meds");
+
+        // Dismissing the ringing alarm keeps the snoozed one
+        press(&mut clock, 'd');
+        assert_eq!(clock.status(), Some("[SNOOZE] Wake up until 07:35"));
+        clock.date_time = Utc.with_ymd_and_hms(2024, 1, 27, 7, 36, 0).unwrap();
+        assert_eq!(
+            step(&mut clock),
+            vec![Effect::Command("wake".to_owned(), Vec::new())]
+        );
+    }
+
+    #[test]
+    fn chimes_ring_on_the_hour() {
+        let mut config = config::load_default();
+        config.timezone = Some(Tz::UTC);
+        config.chimes = Chimes {
+            interval: ChimeInterval::Hour,
+            bell: true,
+            flash: true,
+            command: None,
+            sound: Some("chime.wav".to_owned()),
+            player: "aplay -q".to_owned(),
+        };
+
+        let mut clock = Clock::at(Utc.with_ymd_and_hms(2024, 1, 27, 13, 59, 59).unwrap());
+        clock.check_events(&config);
+        assert!(clock.take_effects().is_empty());
+
+        // A skipped frame still chimes once
+        clock.date_time = Utc.with_ymd_and_hms(2024, 1, 27, 14, 0, 1).unwrap();
+        clock.check_events(&config);
+        assert_eq!(
+            clock.take_effects(),
+            vec![
+                Effect::Bell,
+                Effect::Command("aplay -q".to_owned(), vec!["chime.wav".to_owned()])
+            ]
+        );
+        assert!(clock.chime_flash());
+
+        clock.date_time += Duration::milliseconds(300);
+        clock.check_events(&config);
+        assert!(clock.take_effects().is_empty());
+        assert!(!clock.chime_flash());
+
+        clock.date_time += Duration::seconds(1);
+        assert!(!clock.chime_flash());
+    }
 }
@@ -3,7 +3,7 @@ use std::io::{self, Write};
 use clap::crate_version;
 use crossterm::{
     queue,
-    style::{self, Attribute},
+    style::{self, Attribute, Color},
 };
 
 use crate::config::Config;
@@ -16,6 +16,9 @@ pub fn print_debug_infos(config: &mut Config) -> io::Result<()> {
     print_debug_label("Version")?;
     writeln!(stdout, "{}", crate_version!())?;
 
+    print_debug_label("Profile")?;
+    writeln!(stdout, "{}", config.profile.as_deref().unwrap_or("none"))?;
+
     print_debug_label("FPS")?;
     writeln!(stdout, "{}", config.fps)?;
 
@@ -25,11 +28,79 @@ pub fn print_debug_infos(config: &mut Config) -> io::Result<()> {
     print_debug_label("Date format")?;
     writeln!(stdout, "{}", config.date_format)?;
 
-    print_debug_label("Color scheme")?;
+    print_debug_label("Time zone")?;
+    match config.timezone {
+        Some(timezone) => writeln!(stdout, "{}", timezone.name())?,
+        None => writeln!(stdout, "system")?,
+    }
+
+    print_debug_label("Color support")?;
+    writeln!(stdout, "{:?}", config.color_support)?;
+
+    print_debug_label("Theme")?;
+    writeln!(stdout, "{}", config.theme.as_deref().unwrap_or("none"))?;
+
+    print_color_scheme("Color scheme", config)
+}
+
+// Display the colors of the config as a bar, after a label
+pub fn print_color_scheme(label: &str, config: &mut Config) -> io::Result<()> {
+    let mut stdout = io::stdout();
+
+    print_debug_label(label)?;
+    print_color_bar(config)?;
+
+    writeln!(stdout)?;
+    queue!(stdout, style::ResetColor)?;
+    let _ = stdout.flush();
+
+    Ok(())
+}
+
+// Display the colors of a theme: its digits' bar on its background, and a status in its
+// accent color
+pub fn print_theme(name: &str, config: &mut Config) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    let background = config
+        .background
+        .map_or(Color::Reset, |color| config.color_support.convert(color));
+    let accent = config
+        .color_support
+        .convert(config.elements.status.color.get_value());
+
+    print_debug_label(name)?;
+    queue!(stdout, style::SetBackgroundColor(background))?;
+    write!(stdout, "  ")?;
+    print_color_bar(config)?;
+
+    // The bar leaves its last colors set
+    queue!(
+        stdout,
+        style::SetBackgroundColor(background),
+        style::SetForegroundColor(accent),
+        style::SetAttributes(config.elements.status.attributes)
+    )?;
+    write!(stdout, "  [PAUSE]  ")?;
+
+    queue!(
+        stdout,
+        style::SetAttribute(Attribute::Reset),
+        style::ResetColor
+    )?;
+    writeln!(stdout)?;
+    let _ = stdout.flush();
+
+    Ok(())
+}
+
+fn print_color_bar(config: &mut Config) -> io::Result<()> {
+    let mut stdout = io::stdout();
+
     let width = config.color.get_keys_count();
     // If width is one, it is a single color
     if width == 1 {
-        queue!(stdout, style::SetBackgroundColor(config.color.get_value()))?;
+        let color = config.color_support.convert(config.color.get_value());
+        queue!(stdout, style::SetBackgroundColor(color))?;
         write!(stdout, "{}", " ".repeat(DEBUG_COLOR_DISPLAY_SIZE))?;
     }
     // Otherwhise, it's a gradient
@@ -37,20 +108,18 @@ pub fn print_debug_infos(config: &mut Config) -> io::Result<()> {
         // Use half characters to display two colors in one character using background
         // and foreground
         for _ in 0..width / 2 {
-            queue!(stdout, style::SetForegroundColor(config.color.get_value()))?;
+            let color = config.color_support.convert(config.color.get_value());
+            queue!(stdout, style::SetForegroundColor(color))?;
             config.color.update();
 
-            queue!(stdout, style::SetBackgroundColor(config.color.get_value()))?;
+            let color = config.color_support.convert(config.color.get_value());
+            queue!(stdout, style::SetBackgroundColor(color))?;
             config.color.update();
 
             write!(stdout, "▌")?;
         }
     }
 
-    writeln!(stdout)?;
-    queue!(stdout, style::ResetColor)?;
-    let _ = stdout.flush();
-
     Ok(())
 }
 
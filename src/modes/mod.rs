@@ -1,4 +1,66 @@
+use crossterm::event::Event;
+
+use crate::{
+    config::Config,
+    rendering::{self, buffer::Buffer, layout::Rect},
+};
+
 pub mod chrono;
 pub mod clock;
 pub mod debug;
 pub mod timer;
+pub mod world;
+
+// Something a mode asks the runtime to do, apart from drawing
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Effect {
+    Bell,
+    // Shell command, run in the background, and arguments added to it as they are
+    Command(String, Vec<String>),
+}
+
+pub trait Mode {
+    // Terminal title, updated whenever it changes
+    fn title(&self) -> String;
+
+    // Status displayed under the time, like "[PAUSE]"
+    fn status(&self) -> Option<&str> {
+        None
+    }
+
+    // Called for every terminal event, except CTRL-C which is handled by the runtime
+    fn handle_event(&mut self, _event: &Event) {}
+
+    // Called once per frame, before rendering
+    fn update(&mut self, _config: &Config) {}
+
+    // Effects asked for since the last call, called once per frame
+    fn take_effects(&mut self) -> Vec<Effect> {
+        Vec::new()
+    }
+
+    // Whether the whole screen is drawn in reverse video, to catch the eye
+    fn flash(&self) -> bool {
+        false
+    }
+
+    // Returns the area the time was drawn in, the row right under it is kept for the status
+    fn render(&mut self, buffer: &mut Buffer, config: &Config) -> Rect;
+
+    // Render the mode along with its status, then the frame and the background around them
+    fn draw(&mut self, buffer: &mut Buffer, config: &Config) {
+        let time_area = self.render(buffer, config);
+        if let Some(status) = self.status() {
+            rendering::draw_status(buffer, time_area, status, &config.elements.status);
+        }
+
+        rendering::frame::draw_frame(buffer, &config.frame, &config.elements.border);
+        if let Some(background) = config.background {
+            rendering::fill_background(buffer, background);
+        }
+
+        if self.flash() {
+            rendering::invert(buffer);
+        }
+    }
+}
@@ -1,20 +1,14 @@
-use std::{
-    io::{self, Write},
-    thread,
-    time::{Duration, Instant},
-};
+use std::time::{Duration, Instant};
 
-use crossterm::{
-    event::{self, Event, KeyCode, KeyModifiers},
-    queue,
-    terminal::{self, ClearType},
-};
+use crossterm::event::{Event, KeyCode};
 
+use crate::utils;
 use crate::{
     config::Config,
-    rendering::{self, symbols},
+    rendering::{self, buffer::Buffer, layout::Rect},
 };
-use crate::{eprintln_quit, utils};
+
+use super::Mode;
 
 struct Timer {
     duration: Duration,
@@ -71,81 +65,91 @@ impl Timer {
     }
 }
 
-pub fn main_loop(config: &mut Config, duration: &str) -> io::Result<()> {
-    let mut stdout = io::stdout();
-
-    let duration = parse_duration::parse(duration)
-        .unwrap_or_else(|_| eprintln_quit!("Invalid duration provided"));
-    let mut timer = Timer::new(duration);
-
-    let mut quit = false;
-    while !quit {
-        // Handle events
-        while event::poll(Duration::ZERO)? {
-            match event::read()? {
-                Event::Key(e) => match e.code {
-                    // Handle CTRL-C
-                    KeyCode::Char('c') => {
-                        if e.modifiers.contains(KeyModifiers::CONTROL) {
-                            quit = true;
-                        }
-                    }
-                    // Handle pause
-                    KeyCode::Char(' ') => {
-                        timer.toggle_pause();
-                    }
-                    // Handle reset
-                    KeyCode::Char('r') => {
-                        timer.reset();
-                    }
-                    _ => {}
-                },
-                _ => {}
-            }
-        }
+const STATUS_LINES: i16 = 1;
 
-        // Clear frame
-        queue!(stdout, terminal::Clear(ClearType::All))?;
+pub struct TimerMode {
+    timer: Timer,
+}
 
-        // Render
-        render_frame(&config, &timer)?;
+impl TimerMode {
+    pub fn new(duration: Duration) -> Self {
+        TimerMode {
+            timer: Timer::new(duration),
+        }
+    }
+}
 
-        config.color.update();
+impl Mode for TimerMode {
+    fn title(&self) -> String {
+        "tlock - timer".to_owned()
+    }
 
-        stdout.flush()?;
+    fn status(&self) -> Option<&str> {
+        if self.timer.is_finished() {
+            Some("[FINISHED]")
+        } else if self.timer.is_paused() {
+            Some("[PAUSE]")
+        } else {
+            None
+        }
+    }
 
-        thread::sleep(Duration::from_millis(1000 / config.fps));
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Key(e) = event {
+            match e.code {
+                // Handle pause
+                KeyCode::Char(' ') => {
+                    self.timer.toggle_pause();
+                }
+                // Handle reset
+                KeyCode::Char('r') => {
+                    self.timer.reset();
+                }
+                _ => {}
+            }
+        }
     }
 
-    Ok(())
+    fn render(&mut self, buffer: &mut Buffer, config: &Config) -> Rect {
+        // Display time
+        let remaining = utils::format_duration(self.timer.time_left());
+        rendering::draw_time(
+            buffer,
+            &config.digits,
+            &remaining,
+            STATUS_LINES,
+            &config.elements.digits,
+            &config.elements.colon,
+        )
+    }
 }
 
-fn render_frame(config: &Config, timer: &Timer) -> io::Result<()> {
-    let color = config.color.get_value();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config, rendering::snapshot::assert_snapshot};
 
-    // Display time
-    let remaining = utils::format_duration(timer.time_left());
-    rendering::draw_time(&remaining, color)?;
+    fn render(timer: &mut TimerMode, width: u16, height: u16) -> Buffer {
+        let config = config::load_default();
 
-    let (width, height) = rendering::get_terminal_size()?;
-    let y = height / 2 + symbols::SYMBOL_HEIGHT as i16 / 2 + 2;
+        let mut buffer = Buffer::new(width, height);
+        timer.draw(&mut buffer, &config);
+        buffer
+    }
 
-    // Display finish state
-    if timer.is_finished() {
-        let text = "[FINISHED]";
-        let x = width / 2 - (text.len() as i16) / 2 - 1;
-        let y = y - symbols::SYMBOL_HEIGHT as i16 + symbols::SYMBOL_HEIGHT as i16 / 2 + 1;
+    #[test]
+    fn timer_paused_80x24() {
+        let mut timer = TimerMode::new(Duration::from_secs(4 * 60));
+        timer.timer.toggle_pause();
 
-        rendering::draw_text(text, x, y, color)?;
+        assert_snapshot("timer_paused_80x24", &render(&mut timer, 80, 24));
     }
-    // Display pause state
-    else if timer.is_paused() {
-        let text = "[PAUSE]";
-        let x = width / 2 - (text.len() as i16) / 2 - 1;
-        let y = y - symbols::SYMBOL_HEIGHT as i16 + symbols::SYMBOL_HEIGHT as i16 / 2 + 1;
 
-        rendering::draw_text(text, x, y, color)?;
-    }
+    #[test]
+    fn timer_finished_50x12() {
+        let mut timer = TimerMode::new(Duration::ZERO);
+        timer.timer.end_time = None;
 
-    Ok(())
+        assert_snapshot("timer_finished_50x12", &render(&mut timer, 50, 12));
+    }
 }
@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::{
+    config::Config,
+    rendering::{self, buffer::Buffer, layout, layout::Rect},
+};
+
+use super::Mode;
+
+// Empty row, and the label
+const LABEL_LINES: i16 = 2;
+
+// Clock of the world mode
+pub struct Zone {
+    pub tz: Tz,
+    pub label: String,
+    pub format: String,
+}
+
+impl Zone {
+    // Last part of the zone name, like "New York" for America/New_York
+    pub fn default_label(tz: Tz) -> String {
+        let name = tz.name();
+        name.rsplit('/').next().unwrap_or(name).replace('_', " ")
+    }
+}
+
+pub struct World {
+    date_time: DateTime<Utc>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        World {
+            date_time: Utc::now(),
+        }
+    }
+}
+
+impl Mode for World {
+    fn title(&self) -> String {
+        "tlock - world".to_owned()
+    }
+
+    fn update(&mut self, _config: &Config) {
+        self.date_time = Utc::now();
+    }
+
+    fn render(&mut self, buffer: &mut Buffer, config: &Config) -> Rect {
+        let times: Vec<String> = config
+            .zones
+            .iter()
+            .map(|zone| {
+                let date_time = self.date_time.with_timezone(&zone.tz);
+                date_time.format(&zone.format).to_string()
+            })
+            .collect();
+
+        // Lay out the grid for the widest time
+        let digits = &config.digits;
+        let text_size = times
+            .iter()
+            .map(|time| rendering::time_size(digits, time))
+            .max()
+            .unwrap_or_default();
+        let columns = layout::grid_columns(
+            &digits.scaling,
+            buffer.size(),
+            digits.style.cell_size(),
+            text_size,
+            LABEL_LINES,
+            times.len(),
+        );
+
+        let areas = layout::grid(buffer.size(), times.len(), columns);
+        for ((zone, time), area) in config.zones.iter().zip(&times).zip(areas) {
+            // Each clock is drawn on its own, as if it was alone on the screen
+            let mut cell = Buffer::new(area.width.max(0) as u16, area.height.max(0) as u16);
+            let time_area = rendering::draw_time(
+                &mut cell,
+                digits,
+                time,
+                LABEL_LINES,
+                &config.elements.digits,
+                &config.elements.colon,
+            );
+
+            // The clock of the zone tlock runs in is highlighted
+            let label_style = if config.timezone == Some(zone.tz) {
+                &config.elements.highlight
+            } else {
+                &config.elements.header
+            };
+            rendering::draw_text_centered(
+                &mut cell,
+                &zone.label,
+                time_area.bottom() + 1,
+                label_style,
+            );
+
+            buffer.blit(&cell, area.x, area.y);
+        }
+
+        // There is no status
+        Rect::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{config, rendering::snapshot::assert_snapshot};
+
+    fn render(width: u16, height: u16) -> Buffer {
+        let mut config = config::load_default();
+        config.timezone = Some(Tz::Europe__London);
+        let mut world = World {
+            date_time: Utc.with_ymd_and_hms(2024, 1, 27, 13, 37, 0).unwrap(),
+        };
+
+        let mut buffer = Buffer::new(width, height);
+        world.draw(&mut buffer, &config);
+        buffer
+    }
+
+    #[test]
+    fn world_80x24() {
+        assert_snapshot("world_80x24", &render(80, 24));
+    }
+
+    #[test]
+    fn world_120x16() {
+        assert_snapshot("world_120x16", &render(120, 16));
+    }
+
+    #[test]
+    fn zone_labels_default_to_the_city() {
+        assert_eq!(Zone::default_label(Tz::America__New_York), "New York");
+        assert_eq!(Zone::default_label(Tz::UTC), "UTC");
+    }
+}
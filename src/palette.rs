@@ -0,0 +1,192 @@
+use std::{fs, path::Path};
+
+use crate::rendering::color::parse_hex_color;
+
+type Rgb = (u8, u8, u8);
+
+// Where palette colors are imported from
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PaletteSource {
+    // pywal's colors.json
+    Wal,
+    Xresources,
+    // base16 YAML scheme
+    Base16,
+}
+
+impl PaletteSource {
+    pub fn from_name(name: &str) -> Option<PaletteSource> {
+        match name {
+            "wal" => Some(PaletteSource::Wal),
+            "xresources" => Some(PaletteSource::Xresources),
+            "base16" => Some(PaletteSource::Base16),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaletteSource::Wal => "wal",
+            PaletteSource::Xresources => "xresources",
+            PaletteSource::Base16 => "base16",
+        }
+    }
+}
+
+// The 16 colors of a terminal palette, slots missing from the source are None
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Palette {
+    colors: [Option<Rgb>; 16],
+}
+
+impl Palette {
+    pub fn load(source: PaletteSource, path: &Path) -> Result<Palette, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read {} palette {:?}: {}", source.name(), path, e))?;
+
+        let palette = match source {
+            PaletteSource::Wal => Palette::parse_wal(&content),
+            PaletteSource::Xresources => Palette::parse_xresources(&content),
+            PaletteSource::Base16 => Palette::parse_base16(&content),
+        }
+        .map_err(|e| format!("Invalid {} palette {:?}: {}", source.name(), path, e))?;
+
+        if palette.colors.iter().all(Option::is_none) {
+            return Err(format!(
+                "No colors found in {} palette {:?}",
+                source.name(),
+                path
+            ));
+        }
+
+        Ok(palette)
+    }
+
+    pub fn get(&self, slot: usize) -> Option<Rgb> {
+        self.colors.get(slot).copied().flatten()
+    }
+
+    // {"colors": {"color0": "#282828", ...}}
+    fn parse_wal(content: &str) -> Result<Palette, String> {
+        let json: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        let colors = json
+            .get("colors")
+            .and_then(|colors| colors.as_object())
+            .ok_or("missing 'colors' object")?;
+
+        let mut palette = Palette { colors: [None; 16] };
+        for (slot, color) in palette.colors.iter_mut().enumerate() {
+            if let Some(value) = colors.get(&format!("color{}", slot)) {
+                let value = value.as_str().unwrap_or_default();
+                *color = Some(
+                    parse_hex_color(value)
+                        .ok_or_else(|| format!("invalid color{}: '{}'", slot, value))?,
+                );
+            }
+        }
+
+        Ok(palette)
+    }
+
+    // *.color4: #458588, *color4: #458588 or URxvt.color4: #458588
+    fn parse_xresources(content: &str) -> Result<Palette, String> {
+        let mut palette = Palette { colors: [None; 16] };
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with('!') || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let name = name.trim();
+            let Some(slot) = name
+                .rfind("color")
+                .and_then(|index| name[index + "color".len()..].parse::<usize>().ok())
+                .filter(|&slot| slot < 16)
+            else {
+                continue;
+            };
+
+            let value = value.trim();
+            palette.colors[slot] = Some(
+                parse_hex_color(value)
+                    .ok_or_else(|| format!("line {}: invalid color '{}'", i + 1, value))?,
+            );
+        }
+
+        Ok(palette)
+    }
+
+    // base00: "282828" ... base0F: "d65d0e", slot N is baseNN in hexadecimal
+    fn parse_base16(content: &str) -> Result<Palette, String> {
+        let mut palette = Palette { colors: [None; 16] };
+
+        for (i, line) in content.lines().enumerate() {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let Some(slot) = name
+                .trim()
+                .strip_prefix("base0")
+                .and_then(|slot| usize::from_str_radix(slot, 16).ok())
+                .filter(|&slot| slot < 16)
+            else {
+                continue;
+            };
+
+            // Values may be quoted and followed by a comment
+            let value = value.split('#').next().unwrap_or_default();
+            let value = value.trim().trim_matches(['"', '\'']);
+            palette.colors[slot] = Some(
+                parse_hex_color(value)
+                    .ok_or_else(|| format!("line {}: invalid color '{}'", i + 1, value))?,
+            );
+        }
+
+        Ok(palette)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palettes_are_parsed() {
+        let wal = r##"{
+            "special": { "background": "#1d2021" },
+            "colors": { "color0": "#1d2021", "color4": "#458588", "color15": "#ebdbb2" }
+        }"##;
+        let palette = Palette::parse_wal(wal).unwrap();
+        assert_eq!(palette.get(4), Some((0x45, 0x85, 0x88)));
+        assert_eq!(palette.get(15), Some((0xeb, 0xdb, 0xb2)));
+        assert_eq!(palette.get(5), None);
+
+        let xresources =
+            "! comment\n*.foreground: #ebdbb2\n*.color4: #458588\nURxvt*color12:#83a598\n";
+        let palette = Palette::parse_xresources(xresources).unwrap();
+        assert_eq!(palette.get(4), Some((0x45, 0x85, 0x88)));
+        assert_eq!(palette.get(12), Some((0x83, 0xa5, 0x98)));
+
+        let base16 = "scheme: \"Gruvbox\"\nbase00: \"282828\" # background\nbase0D: '83a598'\n";
+        let palette = Palette::parse_base16(base16).unwrap();
+        assert_eq!(palette.get(0), Some((0x28, 0x28, 0x28)));
+        assert_eq!(palette.get(13), Some((0x83, 0xa5, 0x98)));
+
+        assert!(Palette::parse_base16("base01: nope").is_err());
+    }
+
+    #[test]
+    fn out_of_range_slots_are_ignored() {
+        let base16 = "base010: \"ffffff\"\nbase0F: \"d65d0e\"\n";
+        let palette = Palette::parse_base16(base16).unwrap();
+        assert_eq!(palette.get(15), Some((0xd6, 0x5d, 0x0e)));
+        assert_eq!(palette.get(0), None);
+
+        let palette = Palette::parse_xresources("*.color16: #ffffff\n").unwrap();
+        assert_eq!(palette.get(0), None);
+    }
+}
@@ -0,0 +1,198 @@
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor, queue,
+    style::{self, Attribute, Attributes, Color},
+    terminal::{self, ClearType},
+};
+
+#[cfg(test)]
+use super::buffer::Buffer;
+use super::{buffer::Cell, color::ColorSupport};
+
+// Surface the renderer writes the changed cells to
+pub trait Backend {
+    fn size(&self) -> io::Result<(u16, u16)>;
+
+    fn clear(&mut self) -> io::Result<()>;
+
+    // Cells are given in row-major order
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (i16, i16, &'a Cell)>;
+
+    fn flush(&mut self) -> io::Result<()>;
+
+    // Ring the terminal bell
+    fn bell(&mut self) -> io::Result<()>;
+}
+
+pub struct CrosstermBackend<W: Write> {
+    writer: W,
+    color_support: ColorSupport,
+}
+
+impl<W: Write> CrosstermBackend<W> {
+    pub fn new(writer: W, color_support: ColorSupport) -> CrosstermBackend<W> {
+        CrosstermBackend {
+            writer,
+            color_support,
+        }
+    }
+
+    // Adapt a cell to the colors the terminal can display
+    fn adapt(&self, cell: &Cell) -> Cell {
+        let mut cell = *cell;
+
+        // Without colors, cells lit with a background color are drawn in reverse video
+        if self.color_support == ColorSupport::Monochrome && cell.bg != Color::Reset {
+            if cell.symbol == ' ' {
+                cell.attributes.set(Attribute::Reverse);
+            } else {
+                cell.symbol = '█';
+            }
+        }
+
+        cell.fg = self.color_support.convert(cell.fg);
+        cell.bg = self.color_support.convert(cell.bg);
+
+        cell
+    }
+}
+
+impl<W: Write> Backend for CrosstermBackend<W> {
+    fn size(&self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        queue!(self.writer, terminal::Clear(ClearType::All))
+    }
+
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (i16, i16, &'a Cell)>,
+    {
+        // Last emitted cursor position and style, used to skip redundant escape codes
+        let mut cursor_position: Option<(i16, i16)> = None;
+        let mut style: Option<(Color, Color, Attributes)> = None;
+
+        for (x, y, cell) in content {
+            // Cursor already advances by itself when cells are contiguous
+            if cursor_position != Some((x, y)) {
+                queue!(self.writer, cursor::MoveTo(x as u16, y as u16))?;
+            }
+
+            let cell = self.adapt(cell);
+            write_style(&mut self.writer, &cell, &mut style)?;
+            write!(self.writer, "{}", cell.symbol)?;
+
+            cursor_position = Some((x + 1, y));
+        }
+
+        if style.is_some() {
+            queue!(
+                self.writer,
+                style::SetAttribute(Attribute::Reset),
+                style::ResetColor
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn bell(&mut self) -> io::Result<()> {
+        write!(self.writer, "\x07")
+    }
+}
+
+fn write_style(
+    writer: &mut impl Write,
+    cell: &Cell,
+    style: &mut Option<(Color, Color, Attributes)>,
+) -> io::Result<()> {
+    let (fg, bg, attributes) = match *style {
+        Some(style) => style,
+        None => (Color::Reset, Color::Reset, Attributes::default()),
+    };
+
+    // Attributes can't be removed individually, so reset everything when they change
+    let attributes_changed = style.is_none() || attributes != cell.attributes;
+    if attributes_changed {
+        queue!(writer, style::SetAttribute(Attribute::Reset))?;
+        if !cell.attributes.is_empty() {
+            queue!(writer, style::SetAttributes(cell.attributes))?;
+        }
+    }
+
+    if attributes_changed || fg != cell.fg {
+        queue!(writer, style::SetForegroundColor(cell.fg))?;
+    }
+    if attributes_changed || bg != cell.bg {
+        queue!(writer, style::SetBackgroundColor(cell.bg))?;
+    }
+
+    *style = Some((cell.fg, cell.bg, cell.attributes));
+
+    Ok(())
+}
+
+// In-memory surface, used to check rendered frames in tests
+#[cfg(test)]
+pub struct TestBackend {
+    buffer: Buffer,
+    pub bells: usize,
+}
+
+#[cfg(test)]
+impl TestBackend {
+    pub fn new(width: u16, height: u16) -> TestBackend {
+        TestBackend {
+            buffer: Buffer::new(width, height),
+            bells: 0,
+        }
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+}
+
+#[cfg(test)]
+impl Backend for TestBackend {
+    fn size(&self) -> io::Result<(u16, u16)> {
+        let (width, height) = self.buffer.size();
+        Ok((width as u16, height as u16))
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.buffer.reset();
+        Ok(())
+    }
+
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (i16, i16, &'a Cell)>,
+    {
+        for (x, y, cell) in content {
+            if let Some(target) = self.buffer.get_mut(x, y) {
+                *target = *cell;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn bell(&mut self) -> io::Result<()> {
+        self.bells += 1;
+        Ok(())
+    }
+}
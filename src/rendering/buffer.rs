@@ -0,0 +1,75 @@
+use crossterm::style::{Attributes, Color};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cell {
+    pub symbol: char,
+    pub fg: Color,
+    pub bg: Color,
+    pub attributes: Attributes,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            symbol: ' ',
+            fg: Color::Reset,
+            bg: Color::Reset,
+            attributes: Attributes::default(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Buffer {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    pub fn new(width: u16, height: u16) -> Buffer {
+        Buffer {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    pub fn size(&self) -> (i16, i16) {
+        (self.width as i16, self.height as i16)
+    }
+
+    // Cells outside of the buffer are silently ignored, so callers don't have to clip
+    pub fn get(&self, x: i16, y: i16) -> Option<&Cell> {
+        self.index_of(x, y).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, x: i16, y: i16) -> Option<&mut Cell> {
+        self.index_of(x, y).map(|i| &mut self.cells[i])
+    }
+
+    // Copy the cells of another buffer, with its top left corner at the given cell
+    pub fn blit(&mut self, other: &Buffer, x: i16, y: i16) {
+        let (width, height) = other.size();
+        for oy in 0..height {
+            for ox in 0..width {
+                if let (Some(cell), Some(other)) = (self.get_mut(x + ox, y + oy), other.get(ox, oy))
+                {
+                    *cell = *other;
+                }
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.cells.fill(Cell::default());
+    }
+
+    fn index_of(&self, x: i16, y: i16) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i16 || y >= self.height as i16 {
+            None
+        } else {
+            Some(y as usize * self.width as usize + x as usize)
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+
+use super::{buffer::Buffer, draw_text, layout, time_size, Digits, TextStyle};
+
+// Title, weekday names and up to 6 weeks, so months always take the same room
+pub const MONTH_HEIGHT: i16 = 8;
+// Seven days of two digits, one space apart
+const DAYS_WIDTH: i16 = 20;
+// Cells taken by the week numbers, and between two months
+const WEEK_NUMBER_WIDTH: i16 = 3;
+const MONTH_GAP: i16 = 3;
+// Cells between the calendar and the text it's next to, or rows when it's below it
+pub const GAP_BESIDE: i16 = 4;
+pub const GAP_BELOW: i16 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Placement {
+    None,
+    Below,
+    Beside,
+}
+
+impl Placement {
+    pub fn from_name(name: &str) -> Option<Placement> {
+        match name {
+            "none" => Some(Placement::None),
+            "below" => Some(Placement::Below),
+            "beside" => Some(Placement::Beside),
+            _ => None,
+        }
+    }
+}
+
+// Calendar panel of the clock mode
+pub struct Calendar {
+    pub placement: Placement,
+    pub first_weekday: Weekday,
+    pub week_numbers: bool,
+    // Show the previous and the next months around the current one
+    pub previous: bool,
+    pub next: bool,
+}
+
+// Styles of the parts of the calendar
+pub struct CalendarStyles<'a> {
+    pub days: &'a TextStyle,
+    // Month title, weekday names and week numbers
+    pub header: &'a TextStyle,
+    pub today: &'a TextStyle,
+}
+
+impl Calendar {
+    // Size of the panel in cells
+    pub fn size(&self) -> (i16, i16) {
+        let months = 1 + self.previous as i16 + self.next as i16;
+        (
+            months * self.month_width() + (months - 1) * MONTH_GAP,
+            MONTH_HEIGHT,
+        )
+    }
+
+    fn month_width(&self) -> i16 {
+        if self.week_numbers {
+            DAYS_WIDTH + WEEK_NUMBER_WIDTH
+        } else {
+            DAYS_WIDTH
+        }
+    }
+
+    // Where the calendar goes next to the time and its `lines_below` rows, it's hidden
+    // when they wouldn't fit in the area at the minimum scale
+    pub fn placement_in(
+        &self,
+        area: (i16, i16),
+        digits: &Digits,
+        time: &str,
+        lines_below: i16,
+    ) -> Placement {
+        let (width, height) = area;
+        let (calendar_width, calendar_height) = self.size();
+        let padding = (digits.scaling.padding + digits.scaling.frame) as i16;
+        let cell_size = digits.style.cell_size();
+        let text_size = time_size(digits, time);
+
+        let fits = match self.placement {
+            Placement::None => false,
+            Placement::Below => {
+                width - 2 * padding >= calendar_width
+                    && layout::fits(
+                        &digits.scaling,
+                        area,
+                        cell_size,
+                        text_size,
+                        lines_below + GAP_BELOW + calendar_height,
+                    )
+            }
+            Placement::Beside => {
+                height - 2 * padding >= calendar_height
+                    && layout::fits(
+                        &digits.scaling,
+                        (width - calendar_width - GAP_BESIDE, height),
+                        cell_size,
+                        text_size,
+                        lines_below,
+                    )
+            }
+        };
+
+        if fits {
+            self.placement
+        } else {
+            Placement::None
+        }
+    }
+
+    // First day of each month shown, in order
+    fn months(&self, today: NaiveDate) -> Vec<NaiveDate> {
+        let current = today.with_day(1).unwrap_or(today);
+        let previous = current.checked_sub_months(Months::new(1));
+        let next = current.checked_add_months(Months::new(1));
+
+        [
+            previous.filter(|_| self.previous),
+            Some(current),
+            next.filter(|_| self.next),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+// Draw the months around today's one, with their top left corner at the given cell
+pub fn draw_calendar(
+    buffer: &mut Buffer,
+    calendar: &Calendar,
+    today: NaiveDate,
+    (x, y): (i16, i16),
+    styles: &CalendarStyles,
+) {
+    let mut x = x;
+    for first in calendar.months(today) {
+        draw_month(buffer, calendar, first, today, (x, y), styles);
+        x += calendar.month_width() + MONTH_GAP;
+    }
+}
+
+fn draw_month(
+    buffer: &mut Buffer,
+    calendar: &Calendar,
+    first: NaiveDate,
+    today: NaiveDate,
+    (x, y): (i16, i16),
+    styles: &CalendarStyles,
+) {
+    let title = first.format("%B %Y").to_string();
+    let title_x = x + (calendar.month_width() - title.chars().count() as i16) / 2;
+    draw_text(buffer, &title, title_x, y, styles.header);
+
+    let days_x = if calendar.week_numbers {
+        draw_text(buffer, "Wk", x, y + 1, styles.header);
+        x + WEEK_NUMBER_WIDTH
+    } else {
+        x
+    };
+
+    let mut weekday = calendar.first_weekday;
+    for i in 0..7 {
+        let name: String = weekday.to_string().chars().take(2).collect();
+        draw_text(buffer, &name, days_x + i * 3, y + 1, styles.header);
+        weekday = weekday.succ();
+    }
+
+    // Weeks start on the first weekday on or before the first of the month
+    let offset = first.weekday().days_since(calendar.first_weekday);
+    let mut week_start = first - Duration::days(offset as i64);
+    let mut row = y + 2;
+    while week_start.month() == first.month() || week_start < first {
+        if calendar.week_numbers {
+            // ISO weeks are the ones of their Thursday
+            let thursday_offset = Weekday::Thu.days_since(calendar.first_weekday);
+            let thursday = week_start + Duration::days(thursday_offset as i64);
+            let week = format!("{:>2}", thursday.iso_week().week());
+            draw_text(buffer, &week, x, row, styles.header);
+        }
+
+        for i in 0..7 {
+            let date = week_start + Duration::days(i);
+            if date.month() != first.month() {
+                continue;
+            }
+
+            let style = if date == today {
+                styles.today
+            } else {
+                styles.days
+            };
+            let day = format!("{:>2}", date.day());
+            draw_text(buffer, &day, days_x + i as i16 * 3, row, style);
+        }
+
+        week_start += Duration::days(7);
+        row += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::{Attributes, Color};
+
+    use super::*;
+
+    #[test]
+    fn months_start_on_the_first_weekday() {
+        let calendar = Calendar {
+            placement: Placement::Below,
+            first_weekday: Weekday::Sun,
+            week_numbers: true,
+            previous: true,
+            next: false,
+        };
+        assert_eq!(calendar.size(), (49, MONTH_HEIGHT));
+
+        let style = TextStyle::from(Color::Reset, Attributes::default());
+        let styles = CalendarStyles {
+            days: &style,
+            header: &style,
+            today: &style,
+        };
+        let mut buffer = Buffer::new(49, MONTH_HEIGHT as u16);
+        let today = NaiveDate::from_ymd_opt(2024, 1, 27).unwrap();
+        draw_calendar(&mut buffer, &calendar, today, (0, 0), &styles);
+
+        let row = |y: i16| -> String {
+            (0..49)
+                .map(|x| buffer.get(x, y).map_or(' ', |cell| cell.symbol))
+                .collect()
+        };
+        assert_eq!(row(0), "     December 2023             January 2024      ");
+        assert_eq!(row(1), "Wk Su Mo Tu We Th Fr Sa   Wk Su Mo Tu We Th Fr Sa");
+        // January 1st 2024 is a Monday, in the first ISO week
+        assert_eq!(row(2), "48                 1  2    1     1  2  3  4  5  6");
+        assert_eq!(row(6), "52 24 25 26 27 28 29 30    5 28 29 30 31         ");
+        assert_eq!(row(7), " 1 31                                            ");
+    }
+}
@@ -0,0 +1,156 @@
+use crossterm::style::{Attributes, Color};
+
+use super::buffer::Buffer;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Style {
+    // One pixel per cell, drawn with the background color
+    Block,
+    // Two pixels per cell, stacked vertically: ▀ ▄
+    HalfBlock,
+    // 2x2 pixels per cell: ▘ ▝ ▖ ▗ ...
+    Quadrant,
+    // 2x4 pixels per cell, using braille patterns
+    Braille,
+}
+
+impl Style {
+    pub fn from_name(name: &str) -> Option<Style> {
+        match name {
+            "block" => Some(Style::Block),
+            "half" => Some(Style::HalfBlock),
+            "quadrant" => Some(Style::Quadrant),
+            "braille" => Some(Style::Braille),
+            _ => None,
+        }
+    }
+
+    // Number of pixels in a cell, horizontally and vertically
+    pub fn cell_size(&self) -> (i16, i16) {
+        match self {
+            Style::Block => (1, 1),
+            Style::HalfBlock => (1, 2),
+            Style::Quadrant => (2, 2),
+            Style::Braille => (2, 4),
+        }
+    }
+}
+
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+// Braille dot bit for each pixel of a cell, indexed by [y][x]
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+// Lit pixel, cells get the attributes of the first pixel giving them their color
+type Pixel = (Color, Attributes);
+
+// Pixel grid, rasterized into buffer cells according to a style
+pub struct Canvas {
+    width: i16,
+    height: i16,
+    pixels: Vec<Option<Pixel>>,
+}
+
+impl Canvas {
+    pub fn new(width: i16, height: i16) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![None; width.max(0) as usize * height.max(0) as usize],
+        }
+    }
+
+    pub fn size(&self) -> (i16, i16) {
+        (self.width, self.height)
+    }
+
+    pub fn set(&mut self, x: i16, y: i16, color: Color, attributes: Attributes) {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            self.pixels[y as usize * self.width as usize + x as usize] = Some((color, attributes));
+        }
+    }
+
+    fn get(&self, x: i16, y: i16) -> Option<Pixel> {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            self.pixels[y as usize * self.width as usize + x as usize]
+        } else {
+            None
+        }
+    }
+
+    // Draw the canvas with its top left corner at the given cell
+    pub fn rasterize(&self, buffer: &mut Buffer, x: i16, y: i16, style: Style) {
+        let (cell_width, cell_height) = style.cell_size();
+
+        for cy in 0..(self.height + cell_height - 1) / cell_height {
+            for cx in 0..(self.width + cell_width - 1) / cell_width {
+                let px = cx * cell_width;
+                let py = cy * cell_height;
+
+                let Some(cell) = buffer.get_mut(x + cx, y + cy) else {
+                    continue;
+                };
+
+                match style {
+                    Style::Block => {
+                        // Render cursor at position by setting background color and using space
+                        if let Some((color, attributes)) = self.get(px, py) {
+                            cell.symbol = ' ';
+                            cell.bg = color;
+                            cell.attributes = attributes;
+                        }
+                    }
+                    Style::HalfBlock => match (self.get(px, py), self.get(px, py + 1)) {
+                        (Some(top), Some(bottom)) if top.0 == bottom.0 => {
+                            cell.symbol = '█';
+                            (cell.fg, cell.attributes) = top;
+                        }
+                        (Some(top), Some(bottom)) => {
+                            cell.symbol = '▀';
+                            (cell.fg, cell.attributes) = top;
+                            cell.bg = bottom.0;
+                        }
+                        (Some(top), None) => {
+                            cell.symbol = '▀';
+                            (cell.fg, cell.attributes) = top;
+                        }
+                        (None, Some(bottom)) => {
+                            cell.symbol = '▄';
+                            (cell.fg, cell.attributes) = bottom;
+                        }
+                        (None, None) => {}
+                    },
+                    Style::Quadrant | Style::Braille => {
+                        // A cell only has one foreground color, use the first lit pixel's
+                        let mut bits = 0;
+                        let mut pixel = None;
+                        for oy in 0..cell_height {
+                            for ox in 0..cell_width {
+                                if let Some(c) = self.get(px + ox, py + oy) {
+                                    bits |= if style == Style::Quadrant {
+                                        1 << (oy * 2 + ox)
+                                    } else {
+                                        BRAILLE_DOTS[oy as usize][ox as usize]
+                                    };
+                                    pixel = pixel.or(Some(c));
+                                }
+                            }
+                        }
+
+                        if let Some((color, attributes)) = pixel {
+                            cell.symbol = if style == Style::Quadrant {
+                                QUADRANTS[bits as usize]
+                            } else {
+                                char::from_u32(0x2800 + bits).unwrap()
+                            };
+                            cell.fg = color;
+                            cell.attributes = attributes;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,39 @@
+use std::{env, fs, path::PathBuf};
+
 use crossterm::style::Color;
 
+use super::interpolation::{self, ColorSpace, Easing};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GradientDirection {
+    // Whole text has the same color, changing over time
+    None,
+    Horizontal,
+    Vertical,
+    Diagonal,
+    Radial,
+}
+
+impl GradientDirection {
+    pub fn from_name(name: &str) -> Option<GradientDirection> {
+        match name {
+            "none" => Some(GradientDirection::None),
+            "horizontal" => Some(GradientDirection::Horizontal),
+            "vertical" => Some(GradientDirection::Vertical),
+            "diagonal" => Some(GradientDirection::Diagonal),
+            "radial" => Some(GradientDirection::Radial),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct ComputableColor {
     values: Vec<Color>,
     current: usize,
+    direction: GradientDirection,
+    // Whether spatial gradients also move over time
+    scroll: bool,
 }
 
 impl ComputableColor {
@@ -10,10 +41,18 @@ impl ComputableColor {
         ComputableColor {
             values: vec![color],
             current: 0,
+            direction: GradientDirection::None,
+            scroll: false,
         }
     }
 
-    pub fn update(&mut self) -> () {
+    pub fn with_direction(mut self, direction: GradientDirection, scroll: bool) -> ComputableColor {
+        self.direction = direction;
+        self.scroll = scroll;
+        self
+    }
+
+    pub fn update(&mut self) {
         self.current = (self.current + 1) % self.values.len();
     }
 
@@ -21,79 +60,408 @@ impl ComputableColor {
         *self.values.get(self.current).unwrap()
     }
 
-    pub fn get_keys_count(&self) -> usize {
-        self.values.len()
-    }
-}
+    // Color at a given position in an area, according to the gradient direction
+    pub fn get_value_at(&self, x: i16, y: i16, width: i16, height: i16) -> Color {
+        let x = x as f32 / (width - 1).max(1) as f32;
+        let y = y as f32 / (height - 1).max(1) as f32;
 
-fn clamp01(v: f32) -> f32 {
-    if v < 0.0 {
-        0.0
-    } else if v > 1.0 {
-        1.0
-    } else {
-        v
+        // Position in the gradient, from 0 to 1
+        let t = match self.direction {
+            GradientDirection::None => return self.get_value(),
+            GradientDirection::Horizontal => x,
+            GradientDirection::Vertical => y,
+            GradientDirection::Diagonal => (x + y) / 2.0,
+            GradientDirection::Radial => {
+                // Distance to the center, 1 being the corners
+                let dx = x - 0.5;
+                let dy = y - 0.5;
+                (dx * dx + dy * dy).sqrt() / 0.5_f32.sqrt()
+            }
+        };
+
+        let mut index = (t.clamp(0.0, 1.0) * (self.values.len() - 1) as f32).round() as usize;
+        if self.scroll {
+            index = (index + self.current) % self.values.len();
+        }
+
+        self.values[index]
     }
-}
 
-fn lerp(a: u8, b: u8, t: f32) -> u8 {
-    (a as f32 + (b as f32 - a as f32) as f32 * clamp01(t)) as u8
+    pub fn get_keys_count(&self) -> usize {
+        self.values.len()
+    }
 }
 
-pub fn generate_gradient(keys: Vec<(u8, u8, u8)>, steps: usize) -> ComputableColor {
+// Generate `steps` colors going through all the keys. Segments between two keys get a
+// number of steps proportional to their perceptual length, so the gradient changes at a
+// steady pace
+pub fn generate_gradient(
+    keys: Vec<(u8, u8, u8)>,
+    easings: Vec<Easing>,
+    space: ColorSpace,
+    steps: usize,
+) -> ComputableColor {
     let mut gradient = Vec::with_capacity(steps);
 
-    let step_size = 1.0 / (steps as f32 / (keys.len() as f32 - 1.0));
-    for i in 0..keys.len() - 1 {
-        let current = keys.get(i).unwrap();
-        let next = keys.get(i + 1).unwrap();
+    let mut lengths: Vec<f32> = keys
+        .windows(2)
+        .map(|pair| interpolation::distance(pair[0], pair[1]))
+        .collect();
 
-        let mut t = 0.0;
-        while t <= 1.0 {
-            t += step_size;
+    // Keys are all the same color, give every segment the same length
+    if lengths.iter().sum::<f32>() == 0.0 {
+        lengths.fill(1.0);
+    }
+    let total_length: f32 = lengths.iter().sum();
 
-            let r = lerp(current.0, next.0, t);
-            let g = lerp(current.1, next.1, t);
-            let b = lerp(current.2, next.2, t);
+    for i in 0..steps {
+        let mut position = i as f32 / (steps - 1).max(1) as f32 * total_length;
 
-            gradient.push(Color::Rgb { r, g, b });
+        // Find the segment this step is in
+        let mut segment = 0;
+        while segment + 1 < lengths.len() && position > lengths[segment] {
+            position -= lengths[segment];
+            segment += 1;
         }
+
+        let (r, g, b) = if let Some(&length) = lengths.get(segment) {
+            let easing = easings.get(segment).copied().unwrap_or(Easing::Linear);
+            let t = easing.apply((position / length).clamp(0.0, 1.0));
+
+            interpolation::interpolate(keys[segment], keys[segment + 1], t, space)
+        } else {
+            // Single key
+            keys[0]
+        };
+
+        gradient.push(Color::Rgb { r, g, b });
     }
 
     ComputableColor {
         values: gradient,
         current: 0,
+        direction: GradientDirection::None,
+        scroll: false,
     }
 }
 
-pub fn parse_hex_color(value: &str) -> (u8, u8, u8) {
+// Parse RRGGBB or RGB colors, with an optional leading #
+pub fn parse_hex_color(value: &str) -> Option<(u8, u8, u8)> {
+    let value = value.strip_prefix('#').unwrap_or(value);
+    if !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
     // Expand #XXX colors
     let value = if value.len() == 3 {
-        format!(
-            "{}{}{}{}{}{}",
-            &value[0..1],
-            &value[0..1],
-            &value[1..2],
-            &value[1..2],
-            &value[2..3],
-            &value[2..3]
-        )
+        value.chars().flat_map(|c| [c, c]).collect()
     } else {
         value.to_owned()
     };
 
     if value.len() != 6 {
-        panic!("ERROR: Invalid hex color: {}", value);
+        return None;
+    }
+
+    let extract_component =
+        |index: usize| u8::from_str_radix(&value[index * 2..(index + 1) * 2], 16).ok();
+
+    Some((
+        extract_component(0)?,
+        extract_component(1)?,
+        extract_component(2)?,
+    ))
+}
+
+// Terminal palette, with xterm's default values
+pub const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (205, 0, 0)),
+    (Color::DarkGreen, (0, 205, 0)),
+    (Color::DarkYellow, (205, 205, 0)),
+    (Color::DarkBlue, (0, 0, 238)),
+    (Color::DarkMagenta, (205, 0, 205)),
+    (Color::DarkCyan, (0, 205, 205)),
+    (Color::Grey, (229, 229, 229)),
+    (Color::DarkGrey, (127, 127, 127)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (92, 92, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// Levels of the 6x6x6 color cube of the 256 colors palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorSupport {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    Monochrome,
+}
+
+impl ColorSupport {
+    // "auto" detects the terminal capability
+    pub fn from_name(name: &str) -> Option<ColorSupport> {
+        match name {
+            "auto" => Some(ColorSupport::detect()),
+            "truecolor" => Some(ColorSupport::TrueColor),
+            "256" => Some(ColorSupport::Ansi256),
+            "16" => Some(ColorSupport::Ansi16),
+            "none" => Some(ColorSupport::Monochrome),
+            _ => None,
+        }
+    }
+
+    pub fn detect() -> ColorSupport {
+        let var = |name: &str| env::var(name).unwrap_or_default().to_lowercase();
+
+        // See https://no-color.org
+        if !var("NO_COLOR").is_empty() {
+            return ColorSupport::Monochrome;
+        }
+
+        let colorterm = var("COLORTERM");
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorSupport::TrueColor;
+        }
+
+        let term = var("TERM");
+        if term.is_empty() || term == "dumb" {
+            return ColorSupport::Monochrome;
+        }
+        if term.contains("truecolor") || term.contains("24bit") || term.contains("direct") {
+            return ColorSupport::TrueColor;
+        }
+
+        match terminfo_colors(&term) {
+            Some(colors) if colors >= 1 << 24 => ColorSupport::TrueColor,
+            Some(colors) if colors >= 256 => ColorSupport::Ansi256,
+            Some(colors) if colors >= 8 => ColorSupport::Ansi16,
+            Some(_) => ColorSupport::Monochrome,
+            None if term.contains("256color") => ColorSupport::Ansi256,
+            None => ColorSupport::Ansi16,
+        }
+    }
+
+    // Convert a color to the closest one the terminal can display, Monochrome terminals
+    // get the default color
+    pub fn convert(&self, color: Color) -> Color {
+        match (self, color) {
+            (ColorSupport::Monochrome, _) => Color::Reset,
+            (ColorSupport::TrueColor, _) => color,
+            (ColorSupport::Ansi256, Color::Rgb { r, g, b }) => {
+                Color::AnsiValue(nearest_256((r, g, b)))
+            }
+            (ColorSupport::Ansi16, Color::Rgb { r, g, b }) => nearest_16((r, g, b)),
+            (ColorSupport::Ansi16, Color::AnsiValue(value)) => nearest_16(ansi_256_to_rgb(value)),
+            _ => color,
+        }
+    }
+}
+
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let nearest_level = |v: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(v))
+            .unwrap() as u8
+    };
+
+    // Closest color of the cube, and closest gray of the grayscale ramp
+    let cube = 16 + 36 * nearest_level(rgb.0) + 6 * nearest_level(rgb.1) + nearest_level(rgb.2);
+    let average = (rgb.0 as u16 + rgb.1 as u16 + rgb.2 as u16) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    let distance = |value: u8| interpolation::distance(rgb, ansi_256_to_rgb(value));
+    if distance(gray) < distance(cube) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by(|a, b| {
+            let a = interpolation::distance(rgb, a.1);
+            let b = interpolation::distance(rgb, b.1);
+            a.total_cmp(&b)
+        })
+        .unwrap()
+        .0
+}
+
+fn ansi_256_to_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => ANSI_COLORS[value as usize].1,
+        16..=231 => {
+            let value = value - 16;
+            (
+                CUBE_LEVELS[value as usize / 36],
+                CUBE_LEVELS[value as usize / 6 % 6],
+                CUBE_LEVELS[value as usize % 6],
+            )
+        }
+        _ => {
+            let gray = 8 + 10 * (value - 232);
+            (gray, gray, gray)
+        }
     }
+}
 
-    let extract_component = |index: usize| {
-        u8::from_str_radix(&value[index * 2..(index + 1) * 2], 16)
-            .unwrap_or_else(|_| panic!("error: invalid hex color: {}", value))
+// Read the number of colors from the compiled terminfo entry of a terminal, see term(5)
+fn terminfo_colors(term: &str) -> Option<i32> {
+    const COLORS_INDEX: usize = 13;
+
+    let first = term.chars().next()?;
+
+    let mut directories: Vec<PathBuf> = Vec::new();
+    if let Some(directory) = env::var_os("TERMINFO") {
+        directories.push(directory.into());
+    }
+    if let Some(home) = dirs::home_dir() {
+        directories.push(home.join(".terminfo"));
+    }
+    if let Ok(dirs) = env::var("TERMINFO_DIRS") {
+        directories.extend(dirs.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+    }
+    directories.extend(
+        ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"]
+            .iter()
+            .map(PathBuf::from),
+    );
+
+    // Entries are stored under their first letter, or its hex code on some systems
+    let data = directories.iter().find_map(|directory| {
+        fs::read(directory.join(first.to_string()).join(term))
+            .or_else(|_| fs::read(directory.join(format!("{:x}", first as u32)).join(term)))
+            .ok()
+    })?;
+
+    let read_i16 = |offset: usize| -> Option<i16> {
+        Some(i16::from_le_bytes([
+            *data.get(offset)?,
+            *data.get(offset + 1)?,
+        ]))
     };
 
-    (
-        extract_component(0),
-        extract_component(1),
-        extract_component(2),
-    )
+    // Legacy format stores numbers on 16 bits, the extended one on 32 bits
+    let number_size = match read_i16(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let names_size = read_i16(2)? as usize;
+    let booleans_count = read_i16(4)? as usize;
+    let numbers_count = read_i16(6)? as usize;
+    if COLORS_INDEX >= numbers_count {
+        return None;
+    }
+
+    // Numbers are aligned on an even offset
+    let mut offset = 12 + names_size + booleans_count;
+    offset += offset % 2;
+    offset += COLORS_INDEX * number_size;
+
+    let colors = if number_size == 2 {
+        read_i16(offset)? as i32
+    } else {
+        i32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?)
+    };
+
+    // Negative values mean the capability is absent
+    if colors < 0 {
+        None
+    } else {
+        Some(colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ansi_gradient(direction: GradientDirection, scroll: bool) -> ComputableColor {
+        ComputableColor {
+            values: (0..5).map(Color::AnsiValue).collect(),
+            current: 0,
+            direction,
+            scroll,
+        }
+    }
+
+    #[test]
+    fn horizontal_gradient_spans_the_area() {
+        let color = ansi_gradient(GradientDirection::Horizontal, false);
+
+        assert_eq!(color.get_value_at(0, 3, 9, 5), Color::AnsiValue(0));
+        assert_eq!(color.get_value_at(4, 0, 9, 5), Color::AnsiValue(2));
+        assert_eq!(color.get_value_at(8, 4, 9, 5), Color::AnsiValue(4));
+    }
+
+    #[test]
+    fn radial_gradient_scrolls() {
+        let mut color = ansi_gradient(GradientDirection::Radial, true);
+        assert_eq!(color.get_value_at(4, 4, 9, 9), Color::AnsiValue(0));
+        assert_eq!(color.get_value_at(0, 0, 9, 9), Color::AnsiValue(4));
+
+        color.update();
+        assert_eq!(color.get_value_at(4, 4, 9, 9), Color::AnsiValue(1));
+        assert_eq!(color.get_value_at(0, 0, 9, 9), Color::AnsiValue(0));
+    }
+
+    #[test]
+    fn gradient_has_the_requested_steps() {
+        let keys = vec![(255, 0, 0), (0, 255, 0), (0, 0, 255)];
+        let easings = vec![Easing::EaseIn, Easing::Linear];
+        let mut color = generate_gradient(keys, easings, ColorSpace::Oklab, 10);
+
+        assert_eq!(color.get_keys_count(), 10);
+        assert_eq!(color.get_value(), Color::Rgb { r: 255, g: 0, b: 0 });
+        for _ in 0..9 {
+            color.update();
+        }
+        assert_eq!(color.get_value(), Color::Rgb { r: 0, g: 0, b: 255 });
+    }
+
+    #[test]
+    fn colors_are_quantized() {
+        let orange = Color::Rgb {
+            r: 255,
+            g: 136,
+            b: 0,
+        };
+
+        assert_eq!(ColorSupport::TrueColor.convert(orange), orange);
+        assert_eq!(ColorSupport::Ansi256.convert(orange), Color::AnsiValue(208));
+        assert_eq!(ColorSupport::Ansi16.convert(orange), Color::Red);
+        assert_eq!(ColorSupport::Monochrome.convert(orange), Color::Reset);
+
+        let gray = Color::Rgb {
+            r: 100,
+            g: 100,
+            b: 100,
+        };
+        assert_eq!(ColorSupport::Ansi256.convert(gray), Color::AnsiValue(241));
+        assert_eq!(
+            ColorSupport::Ansi16.convert(Color::AnsiValue(196)),
+            Color::Red
+        );
+    }
+
+    #[test]
+    fn hex_colors_are_parsed() {
+        assert_eq!(parse_hex_color("ff8800"), Some((255, 136, 0)));
+        assert_eq!(parse_hex_color("#f80"), Some((255, 136, 0)));
+        assert_eq!(parse_hex_color("ff880"), None);
+        assert_eq!(parse_hex_color("gg8800"), None);
+        // Multi-byte characters used to panic when slicing
+        assert_eq!(parse_hex_color("é12"), None);
+        assert_eq!(parse_hex_color("ff88é"), None);
+    }
 }
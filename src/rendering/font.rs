@@ -0,0 +1,198 @@
+use std::{collections::HashMap, fs, path::Path};
+
+pub const DEFAULT_FONT: &str = include_str!("../fonts/default.font");
+
+pub struct Glyph {
+    pub width: usize,
+    pixels: Vec<Vec<bool>>,
+}
+
+impl Glyph {
+    fn empty(width: usize, height: usize) -> Glyph {
+        Glyph {
+            width,
+            pixels: vec![vec![false; width]; height],
+        }
+    }
+
+    pub fn is_filled(&self, x: usize, y: usize) -> bool {
+        self.pixels
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(false)
+    }
+}
+
+pub struct Font {
+    pub height: usize,
+    pub spacing: usize,
+    glyphs: HashMap<char, Glyph>,
+    fallback: Glyph,
+}
+
+impl Font {
+    pub fn builtin() -> Font {
+        Font::parse(DEFAULT_FONT).expect("built-in font is valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Font, String> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read font file {:?}: {}", path, e))?;
+
+        Font::parse(&source).map_err(|e| format!("Invalid font file {:?}: {}", path, e))
+    }
+
+    pub fn parse(source: &str) -> Result<Font, String> {
+        let mut metadata: HashMap<&str, usize> = HashMap::new();
+        let mut glyphs: Vec<(usize, &str, Vec<&str>)> = Vec::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim_end();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // New glyph
+            if line.len() > 2 && line.starts_with('[') && line.ends_with(']') {
+                glyphs.push((line_number, &line[1..line.len() - 1], Vec::new()));
+            }
+            // Glyph row
+            else if let Some((_, _, rows)) = glyphs.last_mut() {
+                rows.push(line);
+            }
+            // Metadata
+            else if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim().parse::<usize>().map_err(|_| {
+                    format!("line {}: expected a number, got '{}'", line_number, value)
+                })?;
+                metadata.insert(key.trim(), value);
+            } else {
+                return Err(format!("line {}: unexpected '{}'", line_number, line));
+            }
+        }
+
+        let get_metadata = |key: &str| {
+            metadata
+                .get(key)
+                .copied()
+                .ok_or_else(|| format!("missing '{}'", key))
+        };
+        let width = get_metadata("width")?;
+        let height = get_metadata("height")?;
+        let spacing = metadata.get("spacing").copied().unwrap_or(1);
+
+        let mut font = Font {
+            height,
+            spacing,
+            glyphs: HashMap::new(),
+            fallback: Glyph::empty(width, height),
+        };
+
+        for (line_number, name, rows) in glyphs {
+            let glyph = parse_glyph(&rows, width, height)
+                .map_err(|e| format!("line {}: glyph [{}] {}", line_number, name, e))?;
+
+            let mut chars = name.chars();
+            match (name, chars.next(), chars.next()) {
+                ("space", _, _) => {
+                    font.glyphs.insert(' ', glyph);
+                }
+                ("fallback", _, _) => font.fallback = glyph,
+                (_, Some(c), None) => {
+                    font.glyphs.insert(c, glyph);
+                }
+                _ => return Err(format!("line {}: invalid glyph [{}]", line_number, name)),
+            }
+        }
+
+        Ok(font)
+    }
+
+    pub fn glyph(&self, c: char) -> &Glyph {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.glyphs.get(&c.to_ascii_uppercase()))
+            .unwrap_or(&self.fallback)
+    }
+}
+
+fn parse_glyph(rows: &[&str], width: usize, height: usize) -> Result<Glyph, String> {
+    if rows.len() != height {
+        return Err(format!("has {} rows, expected {}", rows.len(), height));
+    }
+
+    // Glyphs are as wide as their longest row
+    let glyph_width = rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut pixels = Vec::with_capacity(height);
+    for row in rows {
+        if row.chars().count() > width {
+            return Err(format!("row '{}' is wider than {}", row, width));
+        }
+
+        // Short rows are padded with empty pixels
+        let mut parsed = Vec::with_capacity(glyph_width);
+        for c in row.chars() {
+            match c {
+                'X' => parsed.push(true),
+                '.' | ' ' => parsed.push(false),
+                _ => return Err(format!("has invalid pixel '{}'", c)),
+            }
+        }
+        parsed.resize(glyph_width, false);
+
+        pixels.push(parsed);
+    }
+
+    Ok(Glyph {
+        width: glyph_width,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_pads_short_rows() {
+        let font = Font::parse("width=3\nheight=2\n\n[1]\n.X\nXXX\n").unwrap();
+
+        assert_eq!(font.spacing, 1);
+        assert!(font.glyph('1').is_filled(1, 0));
+        assert!(!font.glyph('1').is_filled(2, 0));
+        assert!(font.glyph('1').is_filled(2, 1));
+        assert!(!font.glyph('2').is_filled(0, 1));
+    }
+
+    #[test]
+    fn parse_proportional_glyphs() {
+        let font = Font::parse("width=3\nheight=1\n\n[A]\nXXX\n\n[.]\nX\n").unwrap();
+
+        assert_eq!(font.glyph('A').width, 3);
+        assert_eq!(font.glyph('.').width, 1);
+        // Lowercase letters fall back to uppercase ones
+        assert_eq!(font.glyph('a').width, 3);
+        // Missing glyphs use the fallback, which is as wide as the font by default
+        assert_eq!(font.glyph('?').width, 3);
+    }
+
+    #[test]
+    fn parse_reports_line_numbers() {
+        let error = Font::parse("width=3\nheight=2\n\n[1]\nXXXX\nXXX\n").err();
+        assert_eq!(
+            error.as_deref(),
+            Some("line 4: glyph [1] row 'XXXX' is wider than 3")
+        );
+
+        let error = Font::parse("width=3\n\n[1]\nXXX\n").err();
+        assert_eq!(error.as_deref(), Some("missing 'height'"));
+    }
+}
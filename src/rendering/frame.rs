@@ -0,0 +1,181 @@
+use crossterm::style::Color;
+
+use super::{
+    buffer::{Buffer, Cell},
+    layout::Rect,
+    TextStyle,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Border {
+    None,
+    Single,
+    Rounded,
+    Double,
+    Ascii,
+}
+
+impl Border {
+    pub fn from_name(name: &str) -> Option<Border> {
+        match name {
+            "none" => Some(Border::None),
+            "single" => Some(Border::Single),
+            "rounded" => Some(Border::Rounded),
+            "double" => Some(Border::Double),
+            "ascii" => Some(Border::Ascii),
+            _ => None,
+        }
+    }
+
+    // Top left, top right, bottom left and bottom right corners, then horizontal and
+    // vertical lines
+    fn symbols(&self) -> Option<[char; 6]> {
+        match self {
+            Border::None => None,
+            Border::Single => Some(['┌', '┐', '└', '┘', '─', '│']),
+            Border::Rounded => Some(['╭', '╮', '╰', '╯', '─', '│']),
+            Border::Double => Some(['╔', '╗', '╚', '╝', '═', '║']),
+            Border::Ascii => Some(['+', '+', '+', '+', '-', '|']),
+        }
+    }
+}
+
+// Box drawn around the time and the text under it
+pub struct Frame {
+    pub border: Border,
+    // Empty cells between the border and the content
+    pub padding: i16,
+    // Color filling the inside of the box
+    pub fill: Option<Color>,
+}
+
+impl Frame {
+    // Cells taken by the frame on each side of the content
+    pub fn thickness(&self) -> i16 {
+        match (self.border, self.fill) {
+            (Border::None, None) => 0,
+            (Border::None, Some(_)) => self.padding,
+            _ => self.padding + 1,
+        }
+    }
+}
+
+// Draw the frame around everything drawn in the buffer so far
+pub fn draw_frame(buffer: &mut Buffer, frame: &Frame, style: &TextStyle) {
+    let thickness = frame.thickness();
+    if thickness == 0 {
+        return;
+    }
+    let Some(content) = content_area(buffer) else {
+        return;
+    };
+
+    let area = Rect {
+        x: content.x - thickness,
+        y: content.y - thickness,
+        width: content.width + 2 * thickness,
+        height: content.height + 2 * thickness,
+    };
+    let (right, bottom) = (area.x + area.width - 1, area.bottom() - 1);
+
+    if let Some(fill) = frame.fill {
+        for y in area.y..=bottom {
+            for x in area.x..=right {
+                if let Some(cell) = buffer.get_mut(x, y).filter(|cell| cell.bg == Color::Reset) {
+                    cell.bg = fill;
+                }
+            }
+        }
+    }
+
+    let Some([top_left, top_right, bottom_left, bottom_right, horizontal, vertical]) =
+        frame.border.symbols()
+    else {
+        return;
+    };
+
+    // Spatial gradients go around the box like across the digits
+    let mut set = |x: i16, y: i16, symbol: char| {
+        if let Some(cell) = buffer.get_mut(x, y) {
+            cell.symbol = symbol;
+            cell.fg = style
+                .color
+                .get_value_at(x - area.x, y - area.y, area.width, area.height);
+            cell.attributes = style.attributes;
+        }
+    };
+    for x in area.x + 1..right {
+        set(x, area.y, horizontal);
+        set(x, bottom, horizontal);
+    }
+    for y in area.y + 1..bottom {
+        set(area.x, y, vertical);
+        set(right, y, vertical);
+    }
+    set(area.x, area.y, top_left);
+    set(right, area.y, top_right);
+    set(area.x, bottom, bottom_left);
+    set(right, bottom, bottom_right);
+}
+
+// Smallest area containing every cell that was drawn
+fn content_area(buffer: &Buffer) -> Option<Rect> {
+    let (width, height) = buffer.size();
+    let mut bounds: Option<(i16, i16, i16, i16)> = None;
+
+    for y in 0..height {
+        for x in 0..width {
+            if buffer.get(x, y) == Some(&Cell::default()) {
+                continue;
+            }
+
+            let (left, top, right, bottom) = bounds.unwrap_or((x, y, x, y));
+            bounds = Some((left.min(x), top.min(y), right.max(x), bottom.max(y)));
+        }
+    }
+
+    bounds.map(|(left, top, right, bottom)| Rect {
+        x: left,
+        y: top,
+        width: right - left + 1,
+        height: bottom - top + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Attributes;
+
+    use super::*;
+    use crate::rendering;
+
+    #[test]
+    fn frame_surrounds_the_content() {
+        let mut buffer = Buffer::new(12, 7);
+        let style = TextStyle::from(Color::Reset, Attributes::default());
+        rendering::draw_text(&mut buffer, "tlock", 3, 2, &style);
+        rendering::draw_text(&mut buffer, "abc", 4, 4, &style);
+
+        let frame = Frame {
+            border: Border::Rounded,
+            padding: 1,
+            fill: None,
+        };
+        draw_frame(&mut buffer, &frame, &style);
+
+        let symbol = |x, y| buffer.get(x, y).unwrap().symbol;
+        assert_eq!(symbol(1, 0), '╭');
+        assert_eq!(symbol(9, 0), '╮');
+        assert_eq!(symbol(1, 6), '╰');
+        assert_eq!(symbol(9, 6), '╯');
+        assert_eq!(symbol(5, 0), '─');
+        assert_eq!(symbol(1, 3), '│');
+        assert_eq!(symbol(2, 3), ' ');
+        assert_eq!(symbol(10, 3), ' ');
+
+        // Nothing to frame
+        let mut empty = Buffer::new(4, 4);
+        draw_frame(&mut empty, &frame, &style);
+        assert_eq!(empty, Buffer::new(4, 4));
+    }
+}
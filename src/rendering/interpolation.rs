@@ -0,0 +1,364 @@
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorSpace {
+    Srgb,
+    LinearRgb,
+    Hsl,
+    Hsv,
+    Oklab,
+    Oklch,
+}
+
+impl ColorSpace {
+    pub fn from_name(name: &str) -> Option<ColorSpace> {
+        match name {
+            "srgb" => Some(ColorSpace::Srgb),
+            "linear" => Some(ColorSpace::LinearRgb),
+            "hsl" => Some(ColorSpace::Hsl),
+            "hsv" => Some(ColorSpace::Hsv),
+            "oklab" => Some(ColorSpace::Oklab),
+            "oklch" => Some(ColorSpace::Oklch),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn from_name(name: &str) -> Option<Easing> {
+        match name {
+            "linear" => Some(Easing::Linear),
+            "ease-in" => Some(Easing::EaseIn),
+            "ease-out" => Some(Easing::EaseOut),
+            "ease-in-out" => Some(Easing::EaseInOut),
+            _ => None,
+        }
+    }
+
+    // Easing of the same segment walked backwards, used for looping gradients
+    pub fn reversed(self) -> Easing {
+        match self {
+            Easing::EaseIn => Easing::EaseOut,
+            Easing::EaseOut => Easing::EaseIn,
+            easing => easing,
+        }
+    }
+
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+type Rgb = (u8, u8, u8);
+
+// Saturation or chroma under which a color is a grey, whose hue means nothing
+const ACHROMATIC: f32 = 1e-3;
+
+pub fn interpolate(a: Rgb, b: Rgb, t: f32, space: ColorSpace) -> Rgb {
+    let t = t.clamp(0.0, 1.0);
+
+    match space {
+        ColorSpace::Srgb => from_floats(lerp3(to_floats(a), to_floats(b), t)),
+        ColorSpace::LinearRgb => from_linear(lerp3(to_linear(a), to_linear(b), t)),
+        ColorSpace::Hsl => {
+            let (a, b) = share_hue(to_hsl(a), to_hsl(b), |hsl| hsl.1);
+            from_hsl(lerp_hue(a, b, t))
+        }
+        ColorSpace::Hsv => {
+            let (a, b) = share_hue(to_hsv(a), to_hsv(b), |hsv| hsv.1);
+            from_hsv(lerp_hue(a, b, t))
+        }
+        ColorSpace::Oklab => from_oklab(lerp3(to_oklab(a), to_oklab(b), t)),
+        ColorSpace::Oklch => {
+            // Hue is stored last in LCh, move it first to share the hue interpolation
+            let (l1, c1, h1) = to_oklch(a);
+            let (l2, c2, h2) = to_oklch(b);
+            let (a, b) = share_hue((h1, l1, c1), (h2, l2, c2), |hlc| hlc.2);
+            let (h, l, c) = lerp_hue(a, b, t);
+            from_oklch((l, c, h))
+        }
+    }
+}
+
+// Perceptual distance between two colors
+pub fn distance(a: Rgb, b: Rgb) -> f32 {
+    let a = to_oklab(a);
+    let b = to_oklab(b);
+
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp3(a: (f32, f32, f32), b: (f32, f32, f32), t: f32) -> (f32, f32, f32) {
+    (lerp(a.0, b.0, t), lerp(a.1, b.1, t), lerp(a.2, b.2, t))
+}
+
+// Interpolate colors whose first component is a hue in degrees, taking the shortest path
+// around the hue circle
+fn lerp_hue(a: (f32, f32, f32), b: (f32, f32, f32), t: f32) -> (f32, f32, f32) {
+    let mut delta = (b.0 - a.0) % 360.0;
+    if delta > 180.0 {
+        delta -= 360.0;
+    } else if delta < -180.0 {
+        delta += 360.0;
+    }
+
+    (
+        (a.0 + delta * t).rem_euclid(360.0),
+        lerp(a.1, b.1, t),
+        lerp(a.2, b.2, t),
+    )
+}
+
+// Greys take the hue of the other color, so gradients from or to them don't go through
+// unrelated hues. `chroma` gives the saturation or the chroma of a color
+fn share_hue(
+    mut a: (f32, f32, f32),
+    mut b: (f32, f32, f32),
+    chroma: impl Fn((f32, f32, f32)) -> f32,
+) -> ((f32, f32, f32), (f32, f32, f32)) {
+    match (chroma(a) < ACHROMATIC, chroma(b) < ACHROMATIC) {
+        (true, false) => a.0 = b.0,
+        (false, true) => b.0 = a.0,
+        _ => {}
+    }
+
+    (a, b)
+}
+
+fn to_floats(rgb: Rgb) -> (f32, f32, f32) {
+    (
+        rgb.0 as f32 / 255.0,
+        rgb.1 as f32 / 255.0,
+        rgb.2 as f32 / 255.0,
+    )
+}
+
+fn from_floats(rgb: (f32, f32, f32)) -> Rgb {
+    let component = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    (component(rgb.0), component(rgb.1), component(rgb.2))
+}
+
+fn to_linear(rgb: Rgb) -> (f32, f32, f32) {
+    let component = |v: f32| {
+        if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    };
+
+    let (r, g, b) = to_floats(rgb);
+    (component(r), component(g), component(b))
+}
+
+fn from_linear(rgb: (f32, f32, f32)) -> Rgb {
+    let component = |v: f32| {
+        if v <= 0.0031308 {
+            v * 12.92
+        } else {
+            1.055 * v.powf(1.0 / 2.4) - 0.055
+        }
+    };
+
+    from_floats((component(rgb.0), component(rgb.1), component(rgb.2)))
+}
+
+// Hue, chroma and the min and max components, shared by HSL and HSV conversions
+fn hue_chroma(rgb: Rgb) -> (f32, f32, f32, f32) {
+    let (r, g, b) = to_floats(rgb);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+
+    (hue, chroma, min, max)
+}
+
+fn from_hue_chroma(hue: f32, chroma: f32, m: f32) -> Rgb {
+    let h = hue / 60.0;
+    let x = chroma * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    from_floats((r + m, g + m, b + m))
+}
+
+fn to_hsl(rgb: Rgb) -> (f32, f32, f32) {
+    let (hue, chroma, min, max) = hue_chroma(rgb);
+    let lightness = (max + min) / 2.0;
+    let saturation = if chroma == 0.0 {
+        0.0
+    } else {
+        chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+    };
+
+    (hue, saturation, lightness)
+}
+
+fn from_hsl(hsl: (f32, f32, f32)) -> Rgb {
+    let (hue, saturation, lightness) = hsl;
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+
+    from_hue_chroma(hue, chroma, lightness - chroma / 2.0)
+}
+
+fn to_hsv(rgb: Rgb) -> (f32, f32, f32) {
+    let (hue, chroma, _, max) = hue_chroma(rgb);
+    let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+
+    (hue, saturation, max)
+}
+
+fn from_hsv(hsv: (f32, f32, f32)) -> Rgb {
+    let (hue, saturation, value) = hsv;
+    let chroma = value * saturation;
+
+    from_hue_chroma(hue, chroma, value - chroma)
+}
+
+// See https://bottosson.github.io/posts/oklab/, computed in f64 to keep the precision
+// of the reference matrices
+fn to_oklab(rgb: Rgb) -> (f32, f32, f32) {
+    let (r, g, b) = to_linear(rgb);
+    let (r, g, b) = (r as f64, g as f64, b as f64);
+
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    (
+        (0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s) as f32,
+        (1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s) as f32,
+        (0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s) as f32,
+    )
+}
+
+fn from_oklab(lab: (f32, f32, f32)) -> Rgb {
+    let (l, a, b) = (lab.0 as f64, lab.1 as f64, lab.2 as f64);
+
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+    from_linear((
+        (4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_) as f32,
+        (-1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_) as f32,
+        (-0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_) as f32,
+    ))
+}
+
+fn to_oklch(rgb: Rgb) -> (f32, f32, f32) {
+    let (l, a, b) = to_oklab(rgb);
+
+    (l, a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.0))
+}
+
+fn from_oklch(lch: (f32, f32, f32)) -> Rgb {
+    let (l, c, h) = lch;
+    let h = h.to_radians();
+
+    from_oklab((l, c * h.cos(), c * h.sin()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPACES: [ColorSpace; 6] = [
+        ColorSpace::Srgb,
+        ColorSpace::LinearRgb,
+        ColorSpace::Hsl,
+        ColorSpace::Hsv,
+        ColorSpace::Oklab,
+        ColorSpace::Oklch,
+    ];
+
+    #[test]
+    fn interpolation_keeps_endpoints() {
+        let a = (255, 0, 0);
+        let b = (0, 128, 255);
+
+        for space in SPACES {
+            let start = interpolate(a, b, 0.0, space);
+            let end = interpolate(a, b, 1.0, space);
+
+            for (x, y) in [(start, a), (end, b)] {
+                assert!(
+                    x.0.abs_diff(y.0) <= 1 && x.1.abs_diff(y.1) <= 1 && x.2.abs_diff(y.2) <= 1,
+                    "{:?}: {:?} != {:?}",
+                    space,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn hue_takes_shortest_path() {
+        // Red to magenta goes through pink, not through green and blue
+        let middle = interpolate((255, 0, 0), (255, 0, 255), 0.5, ColorSpace::Hsv);
+        assert_eq!(middle, (255, 0, 128));
+
+        let middle = interpolate((255, 0, 128), (255, 128, 0), 0.5, ColorSpace::Hsv);
+        assert_eq!(middle, (255, 0, 0));
+    }
+
+    #[test]
+    fn greys_take_the_other_hue() {
+        // White to blue keeps the hue of blue, instead of going through magenta
+        let (white, blue) = ((255, 255, 255), (0, 0, 255));
+        for space in [ColorSpace::Hsl, ColorSpace::Hsv, ColorSpace::Oklch] {
+            let hue = |rgb| match space {
+                ColorSpace::Oklch => to_oklch(rgb).2,
+                _ => hue_chroma(rgb).0,
+            };
+
+            for (a, b) in [(white, blue), (blue, white)] {
+                let middle = interpolate(a, b, 0.5, space);
+                assert!(
+                    (hue(middle) - hue(blue)).abs() < 3.0,
+                    "{:?}: {:?}",
+                    space,
+                    middle
+                );
+            }
+        }
+
+        assert_eq!(
+            interpolate(white, blue, 0.5, ColorSpace::Hsl),
+            (159, 159, 223)
+        );
+    }
+}
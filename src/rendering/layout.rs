@@ -0,0 +1,197 @@
+use std::cmp::{max, min};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Rect {
+    pub x: i16,
+    pub y: i16,
+    pub width: i16,
+    pub height: i16,
+}
+
+impl Rect {
+    pub fn bottom(&self) -> i16 {
+        self.y + self.height
+    }
+}
+
+pub struct Scaling {
+    pub min: u16,
+    // 0 means no limit
+    pub max: u16,
+    pub padding: u16,
+    // Cells taken by the frame around the content, on each side
+    pub frame: u16,
+}
+
+// Largest integer scale at which a text of the given size (in pixels) fits in the area
+// (in cells), keeping `lines_below` rows free under it for secondary text
+pub fn compute_scale(
+    scaling: &Scaling,
+    area: (i16, i16),
+    cell_size: (i16, i16),
+    text_size: (i16, i16),
+    lines_below: i16,
+) -> i16 {
+    let (text_width, text_height) = text_size;
+    let (available_width, available_height) = available(scaling, area, cell_size, lines_below);
+
+    let mut scale = min(
+        available_width / max(text_width, 1),
+        available_height / max(text_height, 1),
+    );
+    if scaling.max > 0 {
+        scale = min(scale, scaling.max as i16);
+    }
+
+    max(scale, max(scaling.min as i16, 1))
+}
+
+// Whether a text of the given size (in pixels) fits in the area (in cells) at the
+// minimum scale, keeping `lines_below` rows free under it
+pub fn fits(
+    scaling: &Scaling,
+    area: (i16, i16),
+    cell_size: (i16, i16),
+    text_size: (i16, i16),
+    lines_below: i16,
+) -> bool {
+    let (text_width, text_height) = text_size;
+    let (available_width, available_height) = available(scaling, area, cell_size, lines_below);
+    let scale = max(scaling.min as i16, 1);
+
+    available_width >= text_width * scale && available_height >= text_height * scale
+}
+
+// Room left for the text in pixels, inside the padding and the frame
+fn available(
+    scaling: &Scaling,
+    area: (i16, i16),
+    cell_size: (i16, i16),
+    lines_below: i16,
+) -> (i16, i16) {
+    let (width, height) = area;
+    let (cell_width, cell_height) = cell_size;
+    let padding = (scaling.padding + scaling.frame) as i16;
+
+    (
+        (width - 2 * padding) * cell_width,
+        (height - 2 * padding - lines_below) * cell_height,
+    )
+}
+
+// Center a block of the given size, followed by `lines_below` rows, in the area
+pub fn center(area: (i16, i16), size: (i16, i16), lines_below: i16) -> Rect {
+    let (width, height) = area;
+    let (block_width, block_height) = size;
+
+    Rect {
+        x: (width - block_width) / 2,
+        y: (height - block_height - lines_below) / 2,
+        width: block_width,
+        height: block_height,
+    }
+}
+
+// Number of columns of a grid of `count` texts of the given size (in pixels), making the
+// texts as big as possible in the area (in cells)
+pub fn grid_columns(
+    scaling: &Scaling,
+    area: (i16, i16),
+    cell_size: (i16, i16),
+    text_size: (i16, i16),
+    lines_below: i16,
+    count: usize,
+) -> usize {
+    let (width, height) = area;
+    let (cell_width, cell_height) = cell_size;
+    let (text_width, text_height) = text_size;
+    let padding = (scaling.padding + scaling.frame) as i16;
+
+    // Scales aren't rounded down here, so layouts can be compared even when the text
+    // doesn't fit at all
+    let fit = |columns: usize| {
+        let rows = count.div_ceil(columns);
+        let available_width = (width / columns as i16 - 2 * padding) * cell_width;
+        let available_height = (height / rows as i16 - 2 * padding - lines_below) * cell_height;
+
+        f32::min(
+            available_width as f32 / max(text_width, 1) as f32,
+            available_height as f32 / max(text_height, 1) as f32,
+        )
+    };
+
+    (1..=count.max(1))
+        .max_by(|&a, &b| fit(a).total_cmp(&fit(b)).then(b.cmp(&a)))
+        .unwrap_or(1)
+}
+
+// Areas of a grid of `count` blocks, the last row being centered when it isn't full
+pub fn grid(area: (i16, i16), count: usize, columns: usize) -> Vec<Rect> {
+    let (width, height) = area;
+    let columns = columns.max(1);
+    let rows = count.div_ceil(columns).max(1);
+    let (cell_width, cell_height) = (width / columns as i16, height / rows as i16);
+
+    // Leftover cells are split around the grid
+    let top = (height - cell_height * rows as i16) / 2;
+    (0..count)
+        .map(|i| {
+            let (column, row) = (i % columns, i / columns);
+            let in_row = min(columns, count - row * columns) as i16;
+            let left = (width - cell_width * in_row) / 2;
+
+            Rect {
+                x: left + column as i16 * cell_width,
+                y: top + row as i16 * cell_height,
+                width: cell_width,
+                height: cell_height,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_are_laid_out_to_fit() {
+        let scaling = Scaling {
+            min: 1,
+            max: 0,
+            padding: 1,
+            frame: 0,
+        };
+
+        // Wide terminals get a single row, tall ones a single column
+        let columns = grid_columns(&scaling, (200, 20), (1, 1), (30, 5), 1, 3);
+        assert_eq!(columns, 3);
+        let columns = grid_columns(&scaling, (40, 60), (1, 1), (30, 5), 1, 3);
+        assert_eq!(columns, 1);
+
+        let areas = grid((60, 20), 3, 2);
+        assert_eq!(
+            areas,
+            vec![
+                Rect {
+                    x: 0,
+                    y: 0,
+                    width: 30,
+                    height: 10
+                },
+                Rect {
+                    x: 30,
+                    y: 0,
+                    width: 30,
+                    height: 10
+                },
+                Rect {
+                    x: 15,
+                    y: 10,
+                    width: 30,
+                    height: 10
+                },
+            ]
+        );
+    }
+}
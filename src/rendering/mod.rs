@@ -1,118 +1,203 @@
-use std::{
-    cmp::min,
-    i16,
-    io::{self, Write},
-};
-
-use crossterm::{
-    cursor, queue,
-    style::{self, Attribute, Color},
-    terminal,
+use crossterm::style::{Attribute, Attributes, Color};
+
+use self::{
+    buffer::Buffer,
+    canvas::{Canvas, Style},
+    color::ComputableColor,
+    font::Font,
+    layout::{Rect, Scaling},
 };
 
+pub mod backend;
+pub mod buffer;
+pub mod calendar;
+pub mod canvas;
 pub mod color;
-pub mod symbols;
-
-pub fn get_terminal_size() -> io::Result<(i16, i16)> {
-    let (width, height) = terminal::size()?;
-
-    Ok((width as i16, height as i16))
+pub mod font;
+pub mod frame;
+pub mod interpolation;
+pub mod layout;
+pub mod renderer;
+#[cfg(test)]
+pub mod snapshot;
+
+// How the big text used for the time is drawn
+pub struct Digits {
+    pub font: Font,
+    pub scaling: Scaling,
+    pub style: Style,
+    // Color of the shadow cast by the glyphs, one font pixel down and to the right
+    pub shadow: Option<Color>,
 }
 
-pub fn draw_time(time: &str, color: Color) -> io::Result<()> {
-    let (width, height) = get_terminal_size()?;
-
-    let text_width = draw_time_width(&time);
-    let text_height = 5;
+// Color and attributes of a part of the screen, like the date or the status
+#[derive(Clone)]
+pub struct TextStyle {
+    pub color: ComputableColor,
+    pub attributes: Attributes,
+}
 
-    let mut x = width / 2 - text_width / 2 - 1;
-    let y = height / 2 - text_height / 2 - 1;
-    for c in time.chars() {
-        if c == ':' {
-            x -= 1;
+impl TextStyle {
+    pub fn from(color: Color, attributes: Attributes) -> TextStyle {
+        TextStyle {
+            color: ComputableColor::from(color),
+            attributes,
         }
+    }
+}
 
-        draw_time_symbol(c, x, y, color)?;
-        x += 7;
-
-        if c == ':' {
-            x -= 1;
+// Draw the time as big text, scaled to fill the buffer while keeping `lines_below` rows
+// free under it, and return the area it was drawn in. Colons get their own style
+pub fn draw_time(
+    buffer: &mut Buffer,
+    digits: &Digits,
+    time: &str,
+    lines_below: i16,
+    style: &TextStyle,
+    colon_style: &TextStyle,
+) -> Rect {
+    let font = &digits.font;
+    let (cell_width, cell_height) = digits.style.cell_size();
+
+    let text_size = time_size(digits, time);
+    let scale = layout::compute_scale(
+        &digits.scaling,
+        buffer.size(),
+        digits.style.cell_size(),
+        text_size,
+        lines_below,
+    );
+
+    // Draw the text in pixels, then convert it to cells
+    let mut canvas = Canvas::new(text_size.0 * scale, text_size.1 * scale);
+    if let Some(shadow) = digits.shadow {
+        let style = TextStyle::from(shadow, Attributes::default());
+        let mut x = scale;
+        for c in time.chars() {
+            draw_time_symbol(&mut canvas, font, c, (x, scale), scale, &style);
+            x += (font.glyph(c).width + font.spacing) as i16 * scale;
         }
     }
 
-    Ok(())
+    let mut x = 0;
+    for c in time.chars() {
+        let style = if c == ':' { colon_style } else { style };
+        draw_time_symbol(&mut canvas, font, c, (x, 0), scale, style);
+        x += (font.glyph(c).width + font.spacing) as i16 * scale;
+    }
+
+    let area = layout::center(
+        buffer.size(),
+        (
+            (text_size.0 * scale + cell_width - 1) / cell_width,
+            (text_size.1 * scale + cell_height - 1) / cell_height,
+        ),
+        lines_below,
+    );
+    canvas.rasterize(buffer, area.x, area.y, digits.style);
+
+    area
 }
 
-pub fn draw_text(mut string: &str, mut x: i16, y: i16, color: Color) -> io::Result<()> {
-    let mut stdout = io::stdout();
-    let (width, _) = get_terminal_size()?;
-
-    let offset = if x < 0 {
-        let ret = -x as usize;
-        x = 0;
-        ret
-    } else {
-        0
-    };
-    string = &string[offset..min(string.len(), offset + width as usize)];
-
-    queue!(
-        stdout,
-        cursor::MoveTo(x as u16, y as u16),
-        style::SetForegroundColor(color),
-        style::SetAttribute(Attribute::Bold)
-    )?;
-    write!(stdout, "{}", string)?;
-
-    Ok(())
+pub fn draw_status(buffer: &mut Buffer, time_area: Rect, status: &str, style: &TextStyle) {
+    // Display status right under the time
+    draw_text_centered(buffer, status, time_area.bottom(), style);
 }
 
-fn draw_time_width(time: &str) -> i16 {
-    if time.len() == 0 {
-        return 0;
+pub fn draw_text_centered(buffer: &mut Buffer, string: &str, y: i16, style: &TextStyle) {
+    let (width, _) = buffer.size();
+    let x = (width - string.chars().count() as i16) / 2;
+
+    draw_text(buffer, string, x, y, style);
+}
+
+pub fn draw_text(buffer: &mut Buffer, string: &str, x: i16, y: i16, style: &TextStyle) {
+    // Spatial gradients are spread across the text
+    let length = string.chars().count() as i16;
+    for (i, c) in string.chars().enumerate() {
+        // Out of bounds characters are clipped by the buffer
+        if let Some(cell) = buffer.get_mut(x + i as i16, y) {
+            cell.symbol = c;
+            cell.fg = style.color.get_value_at(i as i16, 0, length, 1);
+            cell.attributes = style.attributes;
+        }
     }
+}
 
-    let mut w = 0;
-    for c in time.chars() {
-        w += if c == ':' {
-            symbols::SYMBOL_HEIGHT
-        } else {
-            symbols::SYMBOL_WIDTH + 1
-        };
+// Swap the foreground and background colors of every cell
+pub fn invert(buffer: &mut Buffer) {
+    let (width, height) = buffer.size();
+    for y in 0..height {
+        for x in 0..width {
+            if let Some(cell) = buffer.get_mut(x, y) {
+                cell.attributes.toggle(Attribute::Reverse);
+            }
+        }
     }
+}
 
-    w -= if time.len() == 1 { 1 } else { 2 };
+// Fill the cells which don't have a background color yet
+pub fn fill_background(buffer: &mut Buffer, color: Color) {
+    let (width, height) = buffer.size();
+    for y in 0..height {
+        for x in 0..width {
+            if let Some(cell) = buffer.get_mut(x, y).filter(|cell| cell.bg == Color::Reset) {
+                cell.bg = color;
+            }
+        }
+    }
+}
 
-    w.try_into().unwrap()
+// Size of the time in font pixels, before scaling
+pub fn time_size(digits: &Digits, time: &str) -> (i16, i16) {
+    // The shadow makes the text one font pixel wider and taller
+    let shadow_size = digits.shadow.map_or(0, |_| 1);
+    (
+        draw_time_width(&digits.font, time) + shadow_size,
+        digits.font.height as i16 + shadow_size,
+    )
 }
 
-fn draw_time_symbol(symbol: char, x: i16, y: i16, color: Color) -> io::Result<()> {
-    let mut stdout = io::stdout();
-    let (width, height) = get_terminal_size()?;
+fn draw_time_width(font: &Font, time: &str) -> i16 {
+    if time.is_empty() {
+        return 0;
+    }
+
+    let width: usize = time
+        .chars()
+        .map(|c| font.glyph(c).width + font.spacing)
+        .sum();
 
-    let data = symbols::symbol_to_render_data(symbol);
+    // No spacing after the last symbol
+    (width - font.spacing) as i16
+}
 
-    for oy in 0..data.len() {
-        for ox in 0..data[oy].len() {
-            if data[oy][ox] {
-                let cx = x + ox as i16;
-                let cy = y + oy as i16;
+fn draw_time_symbol(
+    canvas: &mut Canvas,
+    font: &Font,
+    symbol: char,
+    (x, y): (i16, i16),
+    scale: i16,
+    style: &TextStyle,
+) {
+    let glyph = font.glyph(symbol);
+    let (width, height) = canvas.size();
+
+    for oy in 0..font.height {
+        for ox in 0..glyph.width {
+            if !glyph.is_filled(ox, oy) {
+                continue;
+            }
 
-                if cx < 0 || cx >= width || cy < 0 || cy >= height {
-                    continue;
+            // Each pixel is a square of scale * scale pixels on the canvas
+            for sy in 0..scale {
+                for sx in 0..scale {
+                    let px = x + ox as i16 * scale + sx;
+                    let py = y + oy as i16 * scale + sy;
+                    let color = style.color.get_value_at(px, py, width, height);
+                    canvas.set(px, py, color, style.attributes);
                 }
-
-                // Render cursor at position by setting background color and using space
-                queue!(
-                    stdout,
-                    cursor::MoveTo(cx as u16, cy as u16),
-                    style::SetBackgroundColor(color)
-                )?;
-                write!(stdout, " ")?;
-                queue!(stdout, style::ResetColor)?;
             }
         }
     }
-
-    Ok(())
 }
@@ -0,0 +1,103 @@
+use std::io;
+
+use super::{backend::Backend, buffer::Buffer};
+
+// Double-buffered renderer: frames are drawn into `current`, and only the cells that
+// changed since `previous` are sent to the backend
+pub struct Renderer<B: Backend> {
+    backend: B,
+    current: Buffer,
+    previous: Buffer,
+    force_redraw: bool,
+}
+
+impl<B: Backend> Renderer<B> {
+    pub fn new(backend: B) -> io::Result<Renderer<B>> {
+        let (width, height) = backend.size()?;
+
+        Ok(Renderer {
+            backend,
+            current: Buffer::new(width, height),
+            previous: Buffer::new(width, height),
+            force_redraw: true,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.current = Buffer::new(width, height);
+        self.previous = Buffer::new(width, height);
+        self.force_redraw = true;
+    }
+
+    pub fn buffer_mut(&mut self) -> &mut Buffer {
+        &mut self.current
+    }
+
+    // The bell is sent with the next frame
+    pub fn bell(&mut self) -> io::Result<()> {
+        self.backend.bell()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        if self.force_redraw {
+            self.backend.clear()?;
+        }
+
+        let (width, height) = self.current.size();
+        let previous = &self.previous;
+        let current = &self.current;
+        let force_redraw = self.force_redraw;
+
+        let changes = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter_map(|(x, y)| {
+                let cell = current.get(x, y)?;
+                if !force_redraw && previous.get(x, y) == Some(cell) {
+                    None
+                } else {
+                    Some((x, y, cell))
+                }
+            });
+
+        self.backend.draw(changes)?;
+        self.backend.flush()?;
+
+        // Swap buffers, next frame is drawn from scratch
+        std::mem::swap(&mut self.current, &mut self.previous);
+        self.current.reset();
+        self.force_redraw = false;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::{Attributes, Color};
+
+    use super::*;
+    use crate::rendering::{self, backend::TestBackend, TextStyle};
+
+    #[test]
+    fn flush_clears_stale_cells() {
+        let mut renderer = Renderer::new(TestBackend::new(10, 3)).unwrap();
+        let style = TextStyle::from(Color::Red, Attributes::default());
+
+        rendering::draw_text(renderer.buffer_mut(), "tlock", 0, 1, &style);
+        renderer.flush().unwrap();
+        assert_eq!(renderer.backend().buffer().get(2, 1).unwrap().symbol, 'o');
+
+        rendering::draw_text(renderer.buffer_mut(), "tl", 0, 1, &style);
+        renderer.flush().unwrap();
+        assert_eq!(renderer.backend().buffer().get(1, 1).unwrap().symbol, 'l');
+        assert_eq!(renderer.backend().buffer().get(2, 1).unwrap().symbol, ' ');
+
+        renderer.bell().unwrap();
+        assert_eq!(renderer.backend().bells, 1);
+    }
+}
@@ -0,0 +1,57 @@
+use std::{env, fs, path::PathBuf};
+
+use crossterm::style::Color;
+
+use super::buffer::Buffer;
+
+// Compares a rendered buffer with the snapshot stored in `src/snapshots`. Snapshots are
+// only written when UPDATE_SNAPSHOTS is set, so a missing one fails the test
+pub fn assert_snapshot(name: &str, buffer: &Buffer) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("snapshots")
+        .join(format!("{}.snap", name));
+    let actual = buffer_to_text(buffer);
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "snapshot '{}' is missing, run the tests with UPDATE_SNAPSHOTS=1 to create it\n{}",
+            name, actual
+        )
+    });
+    assert!(
+        expected == actual,
+        "snapshot '{}' does not match\n--- expected\n{}\n--- actual\n{}",
+        name,
+        expected,
+        actual
+    );
+}
+
+// Cells painted with a background color are shown as full blocks, and the frame is
+// surrounded by a border so trailing spaces are visible
+fn buffer_to_text(buffer: &Buffer) -> String {
+    let (width, height) = buffer.size();
+
+    let mut text = format!("+{}+\n", "-".repeat(width as usize));
+    for y in 0..height {
+        text.push('|');
+        for x in 0..width {
+            let cell = buffer.get(x, y).unwrap();
+            if cell.symbol == ' ' && cell.bg != Color::Reset {
+                text.push('█');
+            } else {
+                text.push(cell.symbol);
+            }
+        }
+        text.push_str("|\n");
+    }
+    text.push_str(&format!("+{}+\n", "-".repeat(width as usize)));
+
+    text
+}
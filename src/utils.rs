@@ -1,4 +1,10 @@
-use std::time;
+use std::{
+    process::{Command, Stdio},
+    time,
+};
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use chrono_tz::Tz;
 
 pub fn format_duration(duration: time::Duration) -> String {
     let seconds = duration.as_secs();
@@ -9,6 +15,53 @@ pub fn format_duration(duration: time::Duration) -> String {
     format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
 }
 
+// Format a time in the given zone, or in the system one. Zones give %Z its abbreviation,
+// like CET, while the system one only knows its offset
+pub fn format_date_time(date_time: DateTime<Utc>, zone: Option<Tz>, format: &str) -> String {
+    match zone {
+        Some(zone) => date_time.with_timezone(&zone).format(format).to_string(),
+        None => date_time.with_timezone(&Local).format(format).to_string(),
+    }
+}
+
+// Wall clock time in the given zone, or in the system one
+pub fn local_date_time(date_time: DateTime<Utc>, zone: Option<Tz>) -> NaiveDateTime {
+    match zone {
+        Some(zone) => date_time.with_timezone(&zone).naive_local(),
+        None => date_time.with_timezone(&Local).naive_local(),
+    }
+}
+
+// Run a shell command in the background, its output would mess with the screen
+pub fn spawn_command(command: &str, args: &[String]) {
+    // Failures can't be reported without leaving the screen, they are ignored
+    let _ = shell_command(command, args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
+
+// sh gets the arguments as positional parameters, so it never parses them and file names
+// can't break out of the command. Commands without any are run unchanged
+fn shell_command(command: &str, args: &[String]) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else if args.is_empty() {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", &format!("{} \"$@\"", command), "tlock"]);
+        shell
+    };
+    shell.args(args);
+    shell
+}
+
 #[macro_export]
 macro_rules! eprintln_quit {
     ($($arg:tt)*) => ({
@@ -18,3 +71,19 @@ macro_rules! eprintln_quit {
         std::process::exit(1)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn shell_commands_get_arguments_as_they_are() {
+        let args = ["my \"chime\" $HOME.wav".to_owned(), "two words".to_owned()];
+        let output = shell_command("printf '%s|'", &args).output().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "my \"chime\" $HOME.wav|two words|"
+        );
+    }
+}